@@ -0,0 +1,62 @@
+use bevy::{color::palettes::css, prelude::*};
+
+use crate::{player_controls::AimTarget, AppState, During};
+
+pub struct AimMarkerPlugin;
+
+impl Plugin for AimMarkerPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup_aim_marker);
+        app.add_systems(Update, update_aim_marker.in_set(During::Gameplay));
+        app.add_systems(OnExit(AppState::Game), hide_aim_marker);
+    }
+}
+
+/// A ring around the dweeb the dash would go for.
+#[derive(Component)]
+struct AimMarker;
+
+fn setup_aim_marker(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    commands.spawn((
+        AimMarker,
+        PbrBundle {
+            mesh: meshes.add(Torus::new(0.9, 1.1)),
+            material: materials.add(StandardMaterial {
+                base_color: css::GOLD.into(),
+                unlit: true,
+                ..Default::default()
+            }),
+            visibility: Visibility::Hidden,
+            ..Default::default()
+        },
+    ));
+}
+
+fn update_aim_marker(
+    aim_target: Res<AimTarget>,
+    targets_query: Query<&GlobalTransform>,
+    mut marker_query: Query<(&mut Transform, &mut Visibility), With<AimMarker>>,
+) {
+    let target_position = aim_target
+        .entity
+        .and_then(|entity| targets_query.get(entity).ok())
+        .map(|target_transform| target_transform.translation());
+    for (mut transform, mut visibility) in marker_query.iter_mut() {
+        if let Some(target_position) = target_position {
+            transform.translation = target_position;
+            *visibility = Visibility::Inherited;
+        } else {
+            *visibility = Visibility::Hidden;
+        }
+    }
+}
+
+fn hide_aim_marker(mut marker_query: Query<&mut Visibility, With<AimMarker>>) {
+    for mut visibility in marker_query.iter_mut() {
+        *visibility = Visibility::Hidden;
+    }
+}
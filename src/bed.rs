@@ -1,10 +1,15 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_yoleck::{
-    prelude::*, vpeol::VpeolWillContainClickableChildren, vpeol_3d::Vpeol3dPosition,
+    prelude::*,
+    vpeol::VpeolWillContainClickableChildren,
+    vpeol_3d::{Vpeol3dPosition, Vpeol3dRotation},
 };
 
-use crate::util::affix_vpeol_y;
+use crate::{
+    reservation::{Capacity, Reservations},
+    util::{affix_vpeol_y, edit_vpeol_yaw},
+};
 
 pub struct BedPlugin;
 
@@ -13,9 +18,12 @@ impl Plugin for BedPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Bed")
                 .with::<Vpeol3dPosition>()
-                .insert_on_init(|| Bed)
+                .with::<Vpeol3dRotation>()
+                .with::<Capacity>()
+                .insert_on_init(|| (Bed, Reservations::default()))
         });
         affix_vpeol_y::<With<Bed>>(app, 1.3);
+        edit_vpeol_yaw::<With<Bed>>(app);
         app.add_systems(YoleckSchedule::Populate, populate_bed);
     }
 }
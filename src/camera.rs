@@ -1,9 +1,25 @@
+use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_yoleck::vpeol::prelude::*;
 use dolly::prelude::*;
+use leafwing_input_manager::prelude::*;
 
+use crate::level_rules::LevelRules;
 use crate::player::IsPlayer;
-use crate::During;
+use crate::player_controls::{CameraYaw, PlayerAction};
+use crate::{AppState, During};
+
+/// Zooming changes the length of the arm, but not its direction.
+const DEFAULT_ARM_OFFSET: Vec3 = Vec3::new(0.0, 10.0, 30.0);
+/// In units per second.
+const ZOOM_SPEED: f32 = 20.0;
+/// In units per mouse wheel step.
+const ZOOM_WHEEL_STEP: f32 = 2.0;
+/// How far in front of an occluding obstacle to put the camera.
+const OCCLUSION_MARGIN: f32 = 0.5;
+/// How fast, in units per second, the camera goes back out once it is no longer occluded. Getting
+/// occluded pulls it in immediately.
+const OCCLUSION_RECOVERY_SPEED: f32 = 15.0;
 
 pub struct SwiftDreamsAreMadeForDweebsCameraPlugin;
 
@@ -11,12 +27,43 @@ impl Plugin for SwiftDreamsAreMadeForDweebsCameraPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(Startup, setup_camera);
         app.add_systems(Update, apply_dolly_camera_controls.in_set(During::Gameplay));
+        app.add_systems(OnExit(AppState::Editor), switch_to_gameplay_camera);
+        app.add_systems(OnEnter(AppState::Editor), switch_to_editor_camera);
     }
 }
 
 #[derive(Component)]
 struct CameraController(CameraRig);
 
+/// How far from the player the camera can currently be without furniture or walls blocking the
+/// view.
+#[derive(Component)]
+struct CameraOcclusion {
+    distance: f32,
+}
+
+impl Default for CameraOcclusion {
+    fn default() -> Self {
+        Self {
+            distance: f32::INFINITY,
+        }
+    }
+}
+
+/// Where the designer left the camera in the editor, to restore once the playtest is over.
+#[derive(Component, Default)]
+struct EditorCameraTransform(Option<Transform>);
+
+fn new_camera_rig() -> CameraRig {
+    CameraRig::builder()
+        .with(Position::default())
+        .with(YawPitch::new())
+        .with(Arm::new(DEFAULT_ARM_OFFSET.to_array()))
+        .with(Smooth::new_position(1.0))
+        .with(LookAt::new([0.0, 0.0, 0.0]).tracking_smoothness(0.5))
+        .build()
+}
+
 fn setup_camera(mut commands: Commands) {
     let mut cmd = commands.spawn_empty();
     cmd.insert(Camera3dBundle {
@@ -26,14 +73,9 @@ fn setup_camera(mut commands: Commands) {
     });
     cmd.insert(VpeolCameraState::default());
     cmd.insert(Vpeol3dCameraControl::topdown());
-    cmd.insert(CameraController(
-        CameraRig::builder()
-            .with(Position::default())
-            .with(Arm::new([0.0, 10.0, 30.0]))
-            .with(Smooth::new_position(1.0))
-            .with(LookAt::new([0.0, 0.0, 0.0]).tracking_smoothness(0.5))
-            .build(),
-    ));
+    cmd.insert(CameraController(new_camera_rig()));
+    cmd.insert(CameraOcclusion::default());
+    cmd.insert(EditorCameraTransform::default());
 
     commands.spawn(DirectionalLightBundle {
         directional_light: DirectionalLight {
@@ -47,22 +89,103 @@ fn setup_camera(mut commands: Commands) {
     });
 }
 
+fn switch_to_gameplay_camera(
+    mut query: Query<(
+        &mut CameraController,
+        &mut CameraOcclusion,
+        &mut EditorCameraTransform,
+        &Transform,
+    )>,
+) {
+    for (mut camera_controller, mut camera_occlusion, mut editor_camera_transform, transform) in
+        query.iter_mut()
+    {
+        editor_camera_transform.0 = Some(*transform);
+        // A fresh rig starts right behind the player, instead of swooping from wherever the
+        // previous playtest ended
+        camera_controller.0 = new_camera_rig();
+        *camera_occlusion = Default::default();
+    }
+}
+
+fn switch_to_editor_camera(mut query: Query<(&EditorCameraTransform, &mut Transform)>) {
+    for (editor_camera_transform, mut transform) in query.iter_mut() {
+        if let Some(editor_transform) = editor_camera_transform.0 {
+            *transform = editor_transform;
+        }
+    }
+}
+
 fn apply_dolly_camera_controls(
     time: Res<Time>,
-    mut camera_query: Query<(&mut CameraController, &mut Transform)>,
-    player_query: Query<&GlobalTransform, With<IsPlayer>>,
+    mut camera_query: Query<(&mut CameraController, &mut CameraOcclusion, &mut Transform)>,
+    player_query: Query<(&GlobalTransform, &ActionState<PlayerAction>), With<IsPlayer>>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    spatial_query: SpatialQuery,
+    rigid_bodies_query: Query<&RigidBody>,
+    camera_yaw: Res<CameraYaw>,
 ) {
-    let Ok(player_transform) = player_query.get_single() else {
+    let Ok((player_transform, input)) = player_query.get_single() else {
         return;
     };
+    let level_rules = LevelRules::from_query(&level_rules_query);
     let player_position = player_transform.translation();
-    for (mut camera_controller, mut camera_transform) in camera_query.iter_mut() {
+    // The X axis orbits the camera, which is handled by `CameraYaw`
+    let zoom_input = input
+        .clamped_axis_pair(&PlayerAction::Camera)
+        .map(|axis_pair| axis_pair.y())
+        .unwrap_or_default();
+    let zoom_wheel_steps = input.value(&PlayerAction::ZoomWheel);
+    let look_at = player_position + 3.0 * Vec3::Y;
+    for (mut camera_controller, mut camera_occlusion, mut camera_transform) in
+        camera_query.iter_mut()
+    {
+        camera_controller.0.driver_mut::<YawPitch>().yaw_degrees = camera_yaw.0.to_degrees();
+        let arm = camera_controller.0.driver_mut::<Arm>();
+        let arm_length = Vec3::from_slice(arm.offset.as_ref()).length()
+            - ZOOM_SPEED * zoom_input * time.delta_seconds()
+            - ZOOM_WHEEL_STEP * zoom_wheel_steps;
+        // Not using `clamp` because it panics if a level is misconfigured with min > max
+        let arm_length = arm_length
+            .max(level_rules.min_camera_distance)
+            .min(level_rules.max_camera_distance);
+        arm.offset = (arm_length * DEFAULT_ARM_OFFSET.normalize())
+            .to_array()
+            .into();
+
         camera_controller.0.driver_mut::<Position>().position = player_position.to_array().into();
-        camera_controller.0.driver_mut::<LookAt>().target =
-            (player_position + 3.0 * Vec3::Y).to_array().into();
+        camera_controller.0.driver_mut::<LookAt>().target = look_at.to_array().into();
         camera_controller.0.update(time.delta_seconds());
+
+        // Only the transform gets pulled in, so that the rig keeps the arm length the player chose
+        let rig_position = Vec3::from_slice(camera_controller.0.final_transform.position.as_ref());
+        let vec_to_camera = rig_position - look_at;
+        let rig_distance = vec_to_camera.length();
+        let unoccluded_distance = Dir3::new(vec_to_camera)
+            .ok()
+            .and_then(|direction| {
+                // Dweebs walk in and out of the view all the time (and the player is the one we
+                // want to see) so only static bodies - walls and furniture - count as occluding
+                spatial_query.cast_ray_predicate(
+                    look_at,
+                    direction,
+                    rig_distance,
+                    true,
+                    SpatialQueryFilter::default(),
+                    &|entity| {
+                        rigid_bodies_query
+                            .get(entity)
+                            .is_ok_and(|rigid_body| rigid_body.is_static())
+                    },
+                )
+            })
+            .map(|hit| (hit.time_of_impact - OCCLUSION_MARGIN).max(0.0))
+            .unwrap_or(rig_distance);
+        camera_occlusion.distance = (camera_occlusion.distance
+            + OCCLUSION_RECOVERY_SPEED * time.delta_seconds())
+        .min(unoccluded_distance);
         camera_transform.translation =
-            Vec3::from_slice(camera_controller.0.final_transform.position.as_ref());
+            look_at + vec_to_camera.clamp_length_max(camera_occlusion.distance);
         camera_transform.rotation =
             Quat::from_slice(camera_controller.0.final_transform.rotation.as_ref());
     }
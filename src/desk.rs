@@ -1,10 +1,15 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
 use bevy_yoleck::{
-    prelude::*, vpeol::VpeolWillContainClickableChildren, vpeol_3d::Vpeol3dPosition,
+    prelude::*,
+    vpeol::VpeolWillContainClickableChildren,
+    vpeol_3d::{Vpeol3dPosition, Vpeol3dRotation},
 };
 
-use crate::util::affix_vpeol_y;
+use crate::{
+    reservation::{Capacity, Reservations},
+    util::{affix_vpeol_y, edit_vpeol_yaw},
+};
 
 pub struct DeskPlugin;
 
@@ -13,9 +18,12 @@ impl Plugin for DeskPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Desk")
                 .with::<Vpeol3dPosition>()
-                .insert_on_init(|| Desk)
+                .with::<Vpeol3dRotation>()
+                .with::<Capacity>()
+                .insert_on_init(|| (Desk, Reservations::default()))
         });
         affix_vpeol_y::<With<Desk>>(app, 1.0);
+        edit_vpeol_yaw::<With<Desk>>(app);
         app.add_systems(YoleckSchedule::Populate, populate_desk);
     }
 }
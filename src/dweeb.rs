@@ -1,10 +1,12 @@
 use avian3d::prelude::*;
 use bevy::prelude::*;
+use bevy_egui::egui;
 use bevy_tnua::prelude::*;
 use bevy_tnua_avian3d::TnuaAvian3dSensorShape;
 use bevy_yoleck::{
     prelude::*, vpeol::VpeolWillContainClickableChildren, vpeol_3d::Vpeol3dPosition,
 };
+use serde::{Deserialize, Serialize};
 
 use crate::{player_controls::PotentialAttackTarget, util::affix_vpeol_y};
 
@@ -15,6 +17,7 @@ impl Plugin for DweebPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Dweeb")
                 .with::<Vpeol3dPosition>()
+                .with::<DweebPersonality>()
                 .insert_on_init(|| {
                     (
                         Dweeb {},
@@ -25,6 +28,7 @@ impl Plugin for DweebPlugin {
                 })
         });
         affix_vpeol_y::<With<Dweeb>>(app, 2.0);
+        app.add_yoleck_edit_system(edit_dweeb_personality);
         app.add_systems(YoleckSchedule::Populate, populate_dweeb);
     }
 }
@@ -32,6 +36,42 @@ impl Plugin for DweebPlugin {
 #[derive(Component)]
 pub struct Dweeb {}
 
+/// Per-dweeb tuning, so that levels can mix different kinds of sleepers.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(default)]
+pub struct DweebPersonality {
+    /// Relative to the level's dweeb speed.
+    pub walk_speed: f32,
+    /// Multiplies how long each REM sleep stage lasts.
+    pub rem_duration: f32,
+    /// In seconds.
+    pub scribe_duration: f32,
+    /// How long, in seconds, the dweeb stays startled after waking from REM sleep. Waking from
+    /// non-REM sleep takes twice as long.
+    pub startle_timeout: f32,
+}
+
+impl Default for DweebPersonality {
+    fn default() -> Self {
+        Self {
+            walk_speed: 1.0,
+            rem_duration: 1.0,
+            scribe_duration: 3.0,
+            startle_timeout: 2.0,
+        }
+    }
+}
+
+fn edit_dweeb_personality(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut DweebPersonality>) {
+    let Ok(mut personality) = edit.get_single_mut() else {
+        return;
+    };
+    ui.add(egui::Slider::new(&mut personality.walk_speed, 0.25..=4.0).text("Walk Speed"));
+    ui.add(egui::Slider::new(&mut personality.rem_duration, 0.25..=4.0).text("REM Duration"));
+    ui.add(egui::Slider::new(&mut personality.scribe_duration, 0.5..=10.0).text("Scribe Duration"));
+    ui.add(egui::Slider::new(&mut personality.startle_timeout, 0.5..=10.0).text("Startle Timeout"));
+}
+
 fn populate_dweeb(mut pupulate: YoleckPopulate<(), With<Dweeb>>, asset_server: Res<AssetServer>) {
     pupulate.populate(|ctx, mut cmd, ()| {
         if ctx.is_first_time() {
@@ -1,7 +1,7 @@
 use std::time::Duration;
 
 use bevy::{
-    ecs::query::{QueryData, WorldQuery},
+    ecs::query::{QueryData, ReadOnlyQueryData, WorldQuery},
     prelude::*,
     utils::HashMap,
 };
@@ -9,18 +9,43 @@ use bevy_tnua::{prelude::*, TnuaProximitySensor};
 use bevy_turborand::prelude::*;
 use bevy_yoetz::prelude::*;
 
-use crate::{bed::Bed, desk::Desk, dweeb::Dweeb, dweeb_effects::DweebEffect, score::IncreaseScore};
+#[cfg(test)]
+mod tests;
+
+use crate::{
+    bed::Bed,
+    desk::Desk,
+    dweeb::{Dweeb, DweebPersonality},
+    dweeb_effects::DweebEffect,
+    dweeb_needs::DweebNeeds,
+    gameplay_time::{gameplay_is_running, GameplayTime},
+    level_rules::LevelRules,
+    navigation::{NavGrid, NavPath},
+    player_attack::AttackHit,
+    reservation::{Capacity, Reservations},
+    score::{IncreaseScore, TimePenalty},
+    During,
+};
 
 pub struct DweebBehaviorPlugin;
 
 impl Plugin for DweebBehaviorPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(YoetzPlugin::<DweebBehavior>::new(FixedUpdate));
+        // The behaviors draw from the dweebs' RNGs, so they must not tick while paused or the
+        // replays would go out of sync
+        app.configure_sets(
+            FixedUpdate,
+            (YoetzSystemSet::Suggest, YoetzSystemSet::Act)
+                .in_set(During::Gameplay)
+                .run_if(gameplay_is_running),
+        );
         app.observe(add_behavior_to_dweeb);
         app.add_systems(
             FixedUpdate,
             (
                 suggest_aweken,
+                suggest_startled_by_attack,
                 suggest_idle,
                 suggest_sleep,
                 suggest_walk_to::<Bed>,
@@ -32,6 +57,8 @@ impl Plugin for DweebBehaviorPlugin {
         app.add_systems(
             FixedUpdate,
             (
+                update_reservations::<Bed>,
+                update_reservations::<Desk>,
                 enact_awaken,
                 enact_idle,
                 enact_jump_on_bed,
@@ -42,7 +69,12 @@ impl Plugin for DweebBehaviorPlugin {
             )
                 .in_set(YoetzSystemSet::Act),
         );
-        app.add_systems(FixedUpdate, modify_effect);
+        app.add_systems(
+            FixedUpdate,
+            (update_needs.after(YoetzSystemSet::Act), modify_effect)
+                .in_set(During::Gameplay)
+                .run_if(gameplay_is_running),
+        );
     }
 }
 
@@ -69,6 +101,8 @@ enum DweebBehavior {
         #[yoetz(state)]
         from_rem: bool,
         #[yoetz(state)]
+        by_player: bool,
+        #[yoetz(state)]
         timer: Timer,
     },
     WalkToDesk {
@@ -83,10 +117,42 @@ enum DweebBehavior {
     },
 }
 
-fn gen_walk(direction: Vec3) -> TnuaBuiltinWalk {
+/// A summary of a dweeb's current behavior, for diagnostics.
+pub struct BehaviorDescription {
+    pub name: &'static str,
+    /// The bed or desk the dweeb is currently occupying.
+    pub occupying: Option<Entity>,
+}
+
+pub fn describe_behavior(dweeb: &EntityRef) -> Option<BehaviorDescription> {
+    let (name, occupying) = if dweeb.contains::<DweebBehaviorIdle>() {
+        ("Idle", None)
+    } else if dweeb.contains::<DweebBehaviorWalkToBed>() {
+        ("WalkToBed", None)
+    } else if dweeb.contains::<DweebBehaviorJumpOnBed>() {
+        ("JumpOnBed", None)
+    } else if let Some(sleep) = dweeb.get::<DweebBehaviorSleep>() {
+        ("Sleep", Some(sleep.bed_entity))
+    } else if dweeb.contains::<DweebBehaviorStartled>() {
+        ("Startled", None)
+    } else if dweeb.contains::<DweebBehaviorWalkToDesk>() {
+        ("WalkToDesk", None)
+    } else if let Some(scribe) = dweeb.get::<DweebBehaviorScribe>() {
+        ("Scribe", Some(scribe.desk_entity))
+    } else {
+        return None;
+    };
+    Some(BehaviorDescription { name, occupying })
+}
+
+pub fn is_startled(dweeb: &EntityRef) -> bool {
+    dweeb.contains::<DweebBehaviorStartled>()
+}
+
+fn gen_walk(velocity: Vec3) -> TnuaBuiltinWalk {
     TnuaBuiltinWalk {
-        desired_velocity: 2.5 * direction,
-        desired_forward: direction.normalize_or_zero(),
+        desired_velocity: velocity,
+        desired_forward: velocity.normalize_or_zero(),
         float_height: 2.0,
         // cling_distance: todo!(),
         // spring_strengh: todo!(),
@@ -103,10 +169,27 @@ fn gen_walk(direction: Vec3) -> TnuaBuiltinWalk {
     }
 }
 
-fn add_behavior_to_dweeb(trigger: Trigger<OnInsert, Dweeb>, mut commands: Commands) {
-    commands
-        .entity(trigger.entity())
-        .insert(YoetzAdvisor::<DweebBehavior>::new(10.0));
+fn add_behavior_to_dweeb(
+    trigger: Trigger<OnInsert, Dweeb>,
+    mut commands: Commands,
+    mut global_rng: ResMut<GlobalRng>,
+) {
+    // Each dweeb gets its own RNG so that the AI does not depend on the order in which entities
+    // are iterated or on other users of the global RNG (needed for replays)
+    let mut rng = RngComponent::from(&mut global_rng);
+    commands.entity(trigger.entity()).insert((
+        YoetzAdvisor::<DweebBehavior>::new(10.0),
+        DweebNeeds::random(&mut rng),
+        rng,
+        NavPath::default(),
+        LastWakeUp::default(),
+    ));
+}
+
+/// Remembers how the dweeb last woke up, after it is no longer startled.
+#[derive(Component, Default)]
+struct LastWakeUp {
+    by_player: bool,
 }
 
 fn suggest_idle(mut query: Query<&mut YoetzAdvisor<DweebBehavior>>) {
@@ -136,11 +219,16 @@ trait WalkTo: Component {
 
     const TARGET_DISTANCE: f32;
 
-    type DweebUsesDestinationIndicator: Component;
+    /// How much the dweeb wants to go to this kind of destination. Zero or less means not at all.
+    fn need_weight(needs: &DweebNeeds) -> f32;
+
+    /// The behaviors during which the dweeb holds its reservation of the destination.
+    type Claiming: ReadOnlyQueryData;
 
-    fn extract_entity_in_use(indicator: &Self::DweebUsesDestinationIndicator) -> Entity;
+    fn extract_claimed_entity(claiming: <Self::Claiming as WorldQuery>::Item<'_>)
+        -> Option<Entity>;
     fn suggest_walk_to(destination: Entity) -> DweebBehavior;
-    fn suggest_use(destination: Entity) -> DweebBehavior;
+    fn suggest_use(destination: Entity, personality: &DweebPersonality) -> DweebBehavior;
 
     type Behavior: Component;
 
@@ -156,10 +244,26 @@ impl WalkTo for Bed {
 
     const TARGET_DISTANCE: f32 = 2.0;
 
-    type DweebUsesDestinationIndicator = DweebBehaviorSleep;
+    fn need_weight(needs: &DweebNeeds) -> f32 {
+        // Restlessness also counts, because a dweeb that stood around for too long wants to go
+        // somewhere. Grumpiness only makes the dweeb less eager to go back to bed - even a fully
+        // grumpy dweeb must still be able to get there.
+        (needs.fatigue + 0.25 * needs.restlessness) * (1.0 - 0.5 * needs.grumpiness)
+    }
+
+    type Claiming = AnyOf<(
+        &'static DweebBehaviorWalkToBed,
+        &'static DweebBehaviorJumpOnBed,
+        &'static DweebBehaviorSleep,
+    )>;
 
-    fn extract_entity_in_use(indicator: &Self::DweebUsesDestinationIndicator) -> Entity {
-        indicator.bed_entity
+    fn extract_claimed_entity(
+        (walk_to_bed, jump_on_bed, sleep): <Self::Claiming as WorldQuery>::Item<'_>,
+    ) -> Option<Entity> {
+        walk_to_bed
+            .map(|walk_to_bed| walk_to_bed.bed_entity)
+            .or(jump_on_bed.map(|jump_on_bed| jump_on_bed.bed_entity))
+            .or(sleep.map(|sleep| sleep.bed_entity))
     }
 
     fn suggest_walk_to(destination: Entity) -> DweebBehavior {
@@ -168,7 +272,7 @@ impl WalkTo for Bed {
         }
     }
 
-    fn suggest_use(destination: Entity) -> DweebBehavior {
+    fn suggest_use(destination: Entity, _personality: &DweebPersonality) -> DweebBehavior {
         DweebBehavior::JumpOnBed {
             bed_entity: destination,
         }
@@ -199,14 +303,25 @@ impl WalkTo for Desk {
 
     const TARGET_DISTANCE: f32 = 0.5;
 
+    fn need_weight(needs: &DweebNeeds) -> f32 {
+        needs.inspiration
+    }
+
     fn walk_to_position(transform: &GlobalTransform) -> Vec3 {
         transform.transform_point(1.0 * Vec3::Z)
     }
 
-    type DweebUsesDestinationIndicator = DweebBehaviorScribe;
+    type Claiming = AnyOf<(
+        &'static DweebBehaviorWalkToDesk,
+        &'static DweebBehaviorScribe,
+    )>;
 
-    fn extract_entity_in_use(indicator: &Self::DweebUsesDestinationIndicator) -> Entity {
-        indicator.desk_entity
+    fn extract_claimed_entity(
+        (walk_to_desk, scribe): <Self::Claiming as WorldQuery>::Item<'_>,
+    ) -> Option<Entity> {
+        walk_to_desk
+            .map(|walk_to_desk| walk_to_desk.desk_entity)
+            .or(scribe.map(|scribe| scribe.desk_entity))
     }
 
     fn suggest_walk_to(destination: Entity) -> DweebBehavior {
@@ -215,10 +330,13 @@ impl WalkTo for Desk {
         }
     }
 
-    fn suggest_use(destination: Entity) -> DweebBehavior {
+    fn suggest_use(destination: Entity, personality: &DweebPersonality) -> DweebBehavior {
         DweebBehavior::Scribe {
             desk_entity: destination,
-            timer: Timer::new(Duration::from_secs_f32(3.0), TimerMode::Once),
+            timer: Timer::new(
+                Duration::from_secs_f32(personality.scribe_duration),
+                TimerMode::Once,
+            ),
         }
     }
 
@@ -236,83 +354,105 @@ fn suggest_walk_to<D: WalkTo>(
         &mut YoetzAdvisor<DweebBehavior>,
         D::SuggestableFrom,
         &GlobalTransform,
+        &DweebNeeds,
+        &DweebPersonality,
     )>,
-    destinations_query: Query<(Entity, &D, &GlobalTransform)>,
-    uses_destination_query: Query<&D::DweebUsesDestinationIndicator>,
+    destinations_query: Query<(Entity, &GlobalTransform, &Capacity, &Reservations), With<D>>,
 ) {
-    #[derive(Debug)]
-    struct DestinationStatus {
-        position: Vec3,
-        closest: Option<(Entity, f32)>,
-        demand: f32,
-    }
-    let mut destinations_statuses = HashMap::new();
-    for (destination_entity, _, destination_transform) in destinations_query.iter() {
-        let mut destination_status = DestinationStatus {
-            position: D::walk_to_position(destination_transform),
-            closest: None,
-            demand: 0.0,
-        };
-        for (dweeb_entity, _, _, dweeb_transform) in query.iter() {
-            let distance_sq = dweeb_transform
-                .translation()
-                .distance_squared(destination_status.position);
-            destination_status.demand += 10.0f32.powi(2) / distance_sq;
-            if match destination_status.closest {
-                Some((_, currrent_distance_sq)) => distance_sq < currrent_distance_sq,
-                None => true,
-            } {
-                destination_status.closest = Some((dweeb_entity, distance_sq));
-            }
-        }
-        destinations_statuses.insert(destination_entity, destination_status);
-    }
-    for in_use_indicator in uses_destination_query.iter() {
-        destinations_statuses.remove(&D::extract_entity_in_use(in_use_indicator));
-    }
-
-    for (dweeb_entity, mut advisor, suggestable_from, dweeb_transform) in query.iter_mut() {
+    for (dweeb_entity, mut advisor, suggestable_from, dweeb_transform, needs, personality) in
+        query.iter_mut()
+    {
         if !D::check_suggestable_from(suggestable_from) {
             continue;
         }
-        for (&destination_entity, destination_status) in destinations_statuses.iter() {
-            if destination_status
-                .closest
-                .is_some_and(|(closest_entity, distance_sq)| {
-                    closest_entity != dweeb_entity && distance_sq < 3.0f32.powi(2)
-                })
-            {
+        let need_weight = D::need_weight(needs);
+        if need_weight <= 0.0 {
+            continue;
+        }
+        for (destination_entity, destination_transform, capacity, reservations) in
+            destinations_query.iter()
+        {
+            if !reservations.is_available_for(capacity, dweeb_entity) {
                 continue;
             }
-            let distance_to_destination_sq = destination_status
-                .position
+            let distance_to_destination_sq = D::walk_to_position(destination_transform)
                 .xz()
                 .distance_squared(dweeb_transform.translation().xz());
             if D::TARGET_DISTANCE.powi(2) < distance_to_destination_sq {
                 advisor.suggest(
-                    40.0f32.powi(2) / distance_to_destination_sq,
+                    need_weight * 40.0f32.powi(2) / distance_to_destination_sq,
                     D::suggest_walk_to(destination_entity),
                 );
             } else {
-                advisor.suggest(100.0, D::suggest_use(destination_entity));
+                advisor.suggest(
+                    need_weight * 100.0,
+                    D::suggest_use(destination_entity, personality),
+                );
             }
         }
     }
 }
 
+fn update_reservations<D: WalkTo>(
+    claiming_query: Query<(Entity, D::Claiming)>,
+    mut destinations_query: Query<(Entity, &Capacity, &mut Reservations), With<D>>,
+) {
+    let claims = claiming_query
+        .iter()
+        .filter_map(|(dweeb_entity, claiming)| {
+            Some((dweeb_entity, D::extract_claimed_entity(claiming)?))
+        })
+        .collect::<HashMap<_, _>>();
+    for (destination_entity, _, mut reservations) in destinations_query.iter_mut() {
+        // This also releases the slots of dweebs that were despawned
+        reservations
+            .release_unless(|dweeb_entity| claims.get(&dweeb_entity) == Some(&destination_entity));
+    }
+    // Iterate the query rather than the map, so that the order in which slots are claimed is
+    // deterministic
+    for (dweeb_entity, claiming) in claiming_query.iter() {
+        let Some(destination_entity) = D::extract_claimed_entity(claiming) else {
+            continue;
+        };
+        let Ok((_, capacity, mut reservations)) = destinations_query.get_mut(destination_entity)
+        else {
+            continue;
+        };
+        // If all the slots are taken, the dweeb will not get this destination suggested again and
+        // will move on to something else
+        reservations.claim(capacity, dweeb_entity);
+    }
+}
+
 fn enact_walk_to<D: WalkTo>(
-    mut query: Query<(&mut TnuaController, &GlobalTransform, &D::Behavior)>,
+    mut query: Query<(
+        &mut TnuaController,
+        &GlobalTransform,
+        &D::Behavior,
+        &DweebPersonality,
+        &mut NavPath,
+    )>,
     destination_query: Query<&GlobalTransform>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    nav_grid: Res<NavGrid>,
 ) {
-    for (mut controller, dweeb_transform, walk_to) in query.iter_mut() {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    for (mut controller, dweeb_transform, walk_to, personality, mut nav_path) in query.iter_mut() {
         let Ok(destination_transform) =
             destination_query.get(D::extract_entity_from_behavior(walk_to))
         else {
             continue;
         };
-        let vector = D::walk_to_position(destination_transform) - dweeb_transform.translation();
+        let waypoint = nav_path.steer(
+            &nav_grid,
+            dweeb_transform.translation(),
+            D::walk_to_position(destination_transform),
+        );
+        let vector = waypoint - dweeb_transform.translation();
         let direction = vector.with_y(0.0).normalize_or_zero();
-        controller.basis(gen_walk(direction));
+        controller.basis(gen_walk(
+            level_rules.dweeb_speed * personality.walk_speed * direction,
+        ));
     }
 }
 
@@ -321,10 +461,13 @@ fn enact_jump_on_bed(
         &mut TnuaController,
         &GlobalTransform,
         &DweebBehaviorJumpOnBed,
+        &DweebPersonality,
     )>,
     beds_query: Query<&GlobalTransform>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
 ) {
-    for (mut controller, dweeb_transform, walk_to_bed) in query.iter_mut() {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    for (mut controller, dweeb_transform, walk_to_bed, personality) in query.iter_mut() {
         let Ok(bed_transform) = beds_query.get(walk_to_bed.bed_entity) else {
             continue;
         };
@@ -334,7 +477,7 @@ fn enact_jump_on_bed(
             // To ensure we get to the correct velocity even from a stop
             acceleration: f32::INFINITY,
             air_acceleration: f32::INFINITY,
-            ..gen_walk(direction)
+            ..gen_walk(level_rules.dweeb_speed * personality.walk_speed * direction)
         });
         controller.action(TnuaBuiltinJump {
             height: 2.0,
@@ -355,20 +498,24 @@ fn enact_jump_on_bed(
 
 fn suggest_sleep(
     mut query: Query<(
+        Entity,
         &mut YoetzAdvisor<DweebBehavior>,
         &TnuaController,
         &TnuaProximitySensor,
     )>,
-    beds_query: Query<(), With<Bed>>,
+    beds_query: Query<(&Capacity, &Reservations), With<Bed>>,
 ) {
-    for (mut advisor, controller, sensor) in query.iter_mut() {
+    for (dweeb_entity, mut advisor, controller, sensor) in query.iter_mut() {
         if controller.is_airborne().unwrap_or(true) {
             continue;
         }
         let Some(sensor_output) = sensor.output.as_ref() else {
             continue;
         };
-        if !beds_query.contains(sensor_output.entity) {
+        let Ok((capacity, reservations)) = beds_query.get(sensor_output.entity) else {
+            continue;
+        };
+        if !reservations.is_available_for(capacity, dweeb_entity) {
             continue;
         }
         advisor.suggest(
@@ -387,12 +534,18 @@ fn enact_sleep(
         &mut TnuaController,
         &GlobalTransform,
         &mut DweebBehaviorSleep,
+        &mut RngComponent,
+        &DweebPersonality,
+        &DweebNeeds,
     )>,
     beds_query: Query<&GlobalTransform>,
-    time: Res<Time>,
-    mut global_rng: ResMut<GlobalRng>,
+    time: GameplayTime,
+    level_rules_query: Query<(Entity, &LevelRules)>,
 ) {
-    for (mut controller, dweeb_transform, mut sleep) in query.iter_mut() {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    for (mut controller, dweeb_transform, mut sleep, mut rng, personality, needs) in
+        query.iter_mut()
+    {
         let DweebBehaviorSleep {
             bed_entity,
             stage_is_rem,
@@ -408,12 +561,13 @@ fn enact_sleep(
             float_height: 1.0,
             ..Default::default()
         });
-        *stage_progress += time.delta_seconds()
-            * if *stage_is_rem {
-                0.3 + 0.1 * global_rng.f32_normalized()
-            } else {
-                0.15 + 0.05 * global_rng.f32_normalized()
-            };
+        let rate = if *stage_is_rem {
+            level_rules.rem_rate / personality.rem_duration
+        } else {
+            // Grumpy dweebs take longer to fall into a deep sleep
+            level_rules.non_rem_rate * (1.0 - 0.5 * needs.grumpiness)
+        };
+        *stage_progress += time.delta_seconds() * rate * (1.0 + rng.f32_normalized() / 3.0);
         if 1.0 <= *stage_progress {
             *stage_progress %= 1.0;
             *stage_is_rem = !*stage_is_rem;
@@ -421,6 +575,40 @@ fn enact_sleep(
     }
 }
 
+#[allow(clippy::type_complexity)]
+fn update_needs(
+    mut query: Query<(
+        &mut DweebNeeds,
+        Option<&DweebBehaviorSleep>,
+        Has<DweebBehaviorScribe>,
+        Has<DweebBehaviorWalkToBed>,
+        Has<DweebBehaviorJumpOnBed>,
+        Has<DweebBehaviorWalkToDesk>,
+    )>,
+    time: GameplayTime,
+) {
+    let delta_secs = time.delta_seconds();
+    for (mut needs, sleep, scribe, walk_to_bed, jump_on_bed, walk_to_desk) in query.iter_mut() {
+        // Rates per second, as (fatigue, inspiration, restlessness)
+        let (fatigue, inspiration, restlessness) = if let Some(sleep) = sleep {
+            if sleep.stage_is_rem {
+                (-0.03, 0.1, -0.05)
+            } else {
+                (-0.06, 0.0, -0.05)
+            }
+        } else if scribe {
+            (0.02, -0.3, -0.02)
+        } else if walk_to_bed || jump_on_bed || walk_to_desk {
+            (0.02, -0.01, -0.1)
+        } else {
+            // Idle or startled
+            (0.01, -0.01, 0.05)
+        };
+        needs.change(delta_secs, fatigue, inspiration, restlessness);
+        needs.grumpiness = (needs.grumpiness - 0.02 * delta_secs).max(0.0);
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn modify_effect(
     mut query: Query<
@@ -455,28 +643,42 @@ fn modify_effect(
     }
 }
 
+fn gen_startled(
+    from_rem: bool,
+    by_player: bool,
+    personality: &DweebPersonality,
+    rng: &mut RngComponent,
+) -> DweebBehavior {
+    let base_secs = if from_rem {
+        personality.startle_timeout
+    } else {
+        2.0 * personality.startle_timeout
+    };
+    let wait_secs = base_secs * (1.0 + 0.5 * rng.f32());
+    DweebBehavior::Startled {
+        from_rem,
+        by_player,
+        timer: Timer::new(Duration::from_secs_f32(wait_secs), TimerMode::Once),
+    }
+}
+
 #[allow(clippy::type_complexity)]
 fn suggest_aweken(
     mut query: Query<(
         &mut YoetzAdvisor<DweebBehavior>,
         AnyOf<(&DweebBehaviorSleep, &DweebBehaviorStartled)>,
+        &mut RngComponent,
+        &DweebNeeds,
+        &DweebPersonality,
     )>,
-    mut global_rng: ResMut<GlobalRng>,
 ) {
-    for (mut advisor, (sleep, startled)) in query.iter_mut() {
+    for (mut advisor, (sleep, startled), mut rng, needs, personality) in query.iter_mut() {
         if let Some(sleep) = sleep {
-            let wait_secs = if sleep.stage_is_rem {
-                2.0 + 1.0 * global_rng.f32()
-            } else {
-                4.0 + 2.0 * global_rng.f32()
-            };
             advisor.suggest(
-                // Make it less than Sleep's score, so that if we can sleep it'd override it
-                900.0,
-                DweebBehavior::Startled {
-                    from_rem: sleep.stage_is_rem,
-                    timer: Timer::new(Duration::from_secs_f32(wait_secs), TimerMode::Once),
-                },
+                // Usually less than Sleep's score, so that if we can sleep it'd override it - but
+                // a dweeb that is restless enough and not tired will get up on its own.
+                900.0 * (1.0 + needs.restlessness + needs.grumpiness - needs.fatigue),
+                gen_startled(sleep.stage_is_rem, false, personality, rng.as_mut()),
             )
         } else if let Some(startled) = startled {
             if !startled.timer.finished() {
@@ -484,8 +686,9 @@ fn suggest_aweken(
                     // Make it more than Sleep's score because we are already awake
                     1100.0,
                     DweebBehavior::Startled {
-                        // These fields don't matter because they are both state fields
+                        // These fields don't matter because they are all state fields
                         from_rem: Default::default(),
+                        by_player: Default::default(),
                         timer: Default::default(),
                     },
                 )
@@ -494,12 +697,57 @@ fn suggest_aweken(
     }
 }
 
+/// The least inspiration a dweeb the player woke from REM sleep has.
+const REM_WAKE_INSPIRATION: f32 = 0.5;
+
+fn suggest_startled_by_attack(
+    mut reader: EventReader<AttackHit>,
+    mut query: Query<(
+        &mut YoetzAdvisor<DweebBehavior>,
+        &DweebBehaviorSleep,
+        &mut RngComponent,
+        &DweebPersonality,
+        &mut DweebNeeds,
+    )>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    mut penalty_writer: EventWriter<TimePenalty>,
+) {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    for AttackHit { target, .. } in reader.read() {
+        let Ok((mut advisor, sleep, mut rng, personality, mut needs)) = query.get_mut(*target)
+        else {
+            continue;
+        };
+        if sleep.stage_is_rem {
+            // The dream is what the dweeb goes to scribe, even if it has not been dreaming long
+            // enough to build up inspiration on its own
+            needs.inspiration = needs.inspiration.max(REM_WAKE_INSPIRATION);
+        } else {
+            // Nothing good comes out of waking a dweeb from non-REM sleep
+            needs.grumpiness = (needs.grumpiness + 0.5).min(1.0);
+            penalty_writer.send(TimePenalty {
+                secs: level_rules.non_rem_wake_penalty,
+            });
+        }
+        advisor.suggest(
+            // Make it more than Sleep's score so that the hit wakes the dweeb even on the bed
+            2000.0,
+            gen_startled(sleep.stage_is_rem, true, personality, rng.as_mut()),
+        );
+    }
+}
+
 fn enact_awaken(
-    mut query: Query<(&mut TnuaController, &mut DweebBehaviorStartled)>,
-    time: Res<Time>,
+    mut query: Query<(
+        &mut TnuaController,
+        &mut DweebBehaviorStartled,
+        &mut LastWakeUp,
+    )>,
+    time: GameplayTime,
 ) {
-    for (mut controller, mut startled) in query.iter_mut() {
+    for (mut controller, mut startled, mut last_wake_up) in query.iter_mut() {
         startled.timer.tick(time.delta());
+        last_wake_up.by_player = startled.by_player;
         controller.basis(gen_walk(Vec3::ZERO));
     }
 }
@@ -538,18 +786,26 @@ fn suggest_scribe(
 
 fn enact_scribe(
     mut query: Query<(
+        Entity,
         &mut TnuaController,
         &GlobalTransform,
         &mut DweebBehaviorScribe,
+        &LastWakeUp,
     )>,
     desks_query: Query<&GlobalTransform>,
-    time: Res<Time>,
+    time: GameplayTime,
     mut score_writer: EventWriter<IncreaseScore>,
 ) {
-    for (mut controller, dweeb_transform, mut scribe) in query.iter_mut() {
+    for (dweeb_entity, mut controller, dweeb_transform, mut scribe, last_wake_up) in
+        query.iter_mut()
+    {
         let DweebBehaviorScribe { desk_entity, timer } = scribe.as_mut();
         if timer.tick(time.delta()).finished() {
-            score_writer.send(IncreaseScore);
+            score_writer.send(IncreaseScore {
+                dweeb: dweeb_entity,
+                desk: *desk_entity,
+                woken_by_player: last_wake_up.by_player,
+            });
             continue;
         }
         let Ok(desk_transform) = desks_query.get(*desk_entity) else {
@@ -0,0 +1,403 @@
+use avian3d::prelude::*;
+use bevy::{prelude::*, scene::ScenePlugin, time::TimeUpdateStrategy};
+use bevy_tnua::{controller::TnuaControllerPlugin, prelude::*};
+use bevy_tnua_avian3d::{TnuaAvian3dPlugin, TnuaAvian3dSensorShape};
+use bevy_turborand::prelude::*;
+
+use super::*;
+use crate::{
+    dweeb_needs::DweebNeeds,
+    gameplay_time::GameplayClock,
+    navigation::{Ground, NavigationPlugin},
+    player_attack::AttackHit,
+    reservation::{Capacity, Reservations},
+};
+
+#[derive(Resource, Default)]
+struct ScoreCount(usize);
+
+#[derive(Resource, Default)]
+struct PenaltyCount(usize);
+
+fn create_app() -> App {
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        TransformPlugin,
+        HierarchyPlugin,
+        AssetPlugin::default(),
+        ScenePlugin,
+    ));
+    app.init_asset::<Mesh>();
+    app.add_plugins((
+        PhysicsPlugins::new(FixedPostUpdate),
+        TnuaControllerPlugin::new(FixedUpdate),
+        TnuaAvian3dPlugin::new(FixedUpdate),
+    ));
+    app.add_plugins(RngPlugin::new().with_rng_seed(0));
+    // One fixed timestep per update, regardless of how long the update actually takes
+    app.insert_resource(TimeUpdateStrategy::ManualDuration(
+        Time::<Fixed>::default().timestep(),
+    ));
+
+    app.init_resource::<GameplayClock>();
+    app.add_plugins((DweebBehaviorPlugin, NavigationPlugin));
+    app.add_event::<AttackHit>();
+    app.add_event::<IncreaseScore>();
+    app.add_event::<TimePenalty>();
+    app.init_resource::<ScoreCount>();
+    app.init_resource::<PenaltyCount>();
+    app.add_systems(
+        Update,
+        (
+            |mut reader: EventReader<IncreaseScore>, mut score_count: ResMut<ScoreCount>| {
+                score_count.0 += reader.read().count();
+            },
+            |mut reader: EventReader<TimePenalty>, mut penalty_count: ResMut<PenaltyCount>| {
+                penalty_count.0 += reader.read().count();
+            },
+        ),
+    );
+
+    app.world_mut().spawn((
+        TransformBundle::default(),
+        RigidBody::Static,
+        Collider::cuboid(200.0, 1.0, 200.0),
+        Ground,
+    ));
+    app
+}
+
+fn spawn_dweeb(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position)),
+            Dweeb {},
+            DweebPersonality::default(),
+            RigidBody::Dynamic,
+            Collider::capsule(0.5, 1.0),
+            TnuaControllerBundle::default(),
+            TnuaAvian3dSensorShape(Collider::cuboid(0.45, 0.0, 0.45)),
+        ))
+        .id()
+}
+
+fn spawn_bed(app: &mut App, position: Vec3, capacity: usize) -> Entity {
+    app.world_mut()
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position)),
+            Bed,
+            Capacity { dweebs: capacity },
+            Reservations::default(),
+            RigidBody::Static,
+            Collider::cuboid(1.00, 0.7, 2.0),
+        ))
+        .id()
+}
+
+fn spawn_desk(app: &mut App, position: Vec3) -> Entity {
+    app.world_mut()
+        .spawn((
+            TransformBundle::from_transform(Transform::from_translation(position)),
+            Desk,
+            Capacity::default(),
+            Reservations::default(),
+            RigidBody::Static,
+            Collider::cuboid(2.00, 1.0, 0.8),
+        ))
+        .id()
+}
+
+fn spawn_wall(app: &mut App, position: Vec3, size: Vec3) {
+    app.world_mut().spawn((
+        TransformBundle::from_transform(Transform::from_translation(position)),
+        RigidBody::Static,
+        Collider::cuboid(size.x, size.y, size.z),
+    ));
+}
+
+fn behavior_of(app: &App, dweeb: Entity) -> Option<&'static str> {
+    Some(describe_behavior(&app.world().entity(dweeb))?.name)
+}
+
+/// Runs the app until the dweeb reaches the `until` behavior, and returns the behaviors it went
+/// through on the way (without consecutive duplicates).
+fn run_until_behavior(
+    app: &mut App,
+    dweeb: Entity,
+    until: &'static str,
+    max_ticks: usize,
+) -> Vec<&'static str> {
+    let mut history = Vec::new();
+    for _ in 0..max_ticks {
+        app.update();
+        let Some(behavior) = behavior_of(app, dweeb) else {
+            continue;
+        };
+        if history.last() != Some(&behavior) {
+            history.push(behavior);
+        }
+        if behavior == until {
+            return history;
+        }
+    }
+    panic!("Dweeb did not reach {until} within {max_ticks} ticks. Went through {history:?}");
+}
+
+/// Checks that the history goes through the expected behaviors in order, and only through them.
+///
+/// Going back and forth between two consecutive expected behaviors is allowed, because physics
+/// can make a dweeb bounce around its target.
+fn assert_chain(history: &[&'static str], expected: &[&'static str]) {
+    for behavior in history {
+        assert!(
+            expected.contains(behavior),
+            "Unexpected behavior {behavior} in {history:?} (expected {expected:?})"
+        );
+    }
+    let mut remaining = expected.iter().peekable();
+    for behavior in history {
+        if remaining.peek() == Some(&behavior) {
+            remaining.next();
+        }
+    }
+    assert!(
+        remaining.peek().is_none(),
+        "Expected chain {expected:?} but got {history:?}"
+    );
+}
+
+fn put_dweeb_to_sleep(app: &mut App) -> (Entity, Entity) {
+    let dweeb = spawn_dweeb(app, Vec3::new(0.0, 2.0, 0.0));
+    let history = run_until_behavior(app, dweeb, "Idle", 64);
+    assert_eq!(history, ["Idle"]);
+
+    let bed = spawn_bed(app, Vec3::new(5.0, 1.3, 0.0), 1);
+    let history = run_until_behavior(app, dweeb, "Sleep", 64 * 20);
+    assert_chain(&history, &["Idle", "WalkToBed", "JumpOnBed", "Sleep"]);
+    assert_eq!(
+        app.world()
+            .get::<DweebBehaviorSleep>(dweeb)
+            .unwrap()
+            .bed_entity,
+        bed
+    );
+    (dweeb, bed)
+}
+
+fn startle_dweeb(app: &mut App, dweeb: Entity, from_rem: bool) {
+    let mut sleep = app
+        .world_mut()
+        .get_mut::<DweebBehaviorSleep>(dweeb)
+        .unwrap();
+    sleep.stage_is_rem = from_rem;
+    sleep.stage_progress = 0.0;
+    // REM sleep is what gives dweebs the inspiration to go to a desk
+    *app.world_mut().get_mut::<DweebNeeds>(dweeb).unwrap() = DweebNeeds {
+        fatigue: 0.5,
+        inspiration: if from_rem { 1.0 } else { 0.0 },
+        restlessness: 0.0,
+        grumpiness: 0.0,
+    };
+    hit_dweeb(app, dweeb, from_rem);
+}
+
+fn hit_dweeb(app: &mut App, dweeb: Entity, from_rem: bool) {
+    app.world_mut().send_event(AttackHit {
+        attacker: Entity::PLACEHOLDER,
+        target: dweeb,
+    });
+    run_until_behavior(app, dweeb, "Startled", 4);
+    assert_eq!(
+        app.world()
+            .get::<DweebBehaviorStartled>(dweeb)
+            .unwrap()
+            .from_rem,
+        from_rem
+    );
+
+    // Knock the dweeb off the bed, like the player's dash would
+    let mut transform = app.world_mut().get_mut::<Transform>(dweeb).unwrap();
+    transform.translation = Vec3::new(0.0, 2.0, 0.0);
+}
+
+#[test]
+fn dweeb_walks_to_bed_and_sleeps() {
+    let mut app = create_app();
+    put_dweeb_to_sleep(&mut app);
+}
+
+#[test]
+fn dweeb_startled_from_rem_scribes_an_idea() {
+    let mut app = create_app();
+    let desk = spawn_desk(&mut app, Vec3::new(-5.0, 1.0, 0.0));
+    let (dweeb, _) = put_dweeb_to_sleep(&mut app);
+    startle_dweeb(&mut app, dweeb, true);
+
+    let history = run_until_behavior(&mut app, dweeb, "Scribe", 64 * 20);
+    assert_chain(&history, &["Startled", "WalkToDesk", "Scribe"]);
+    assert_eq!(
+        app.world()
+            .get::<DweebBehaviorScribe>(dweeb)
+            .unwrap()
+            .desk_entity,
+        desk
+    );
+
+    assert_eq!(app.world().resource::<ScoreCount>().0, 0);
+    for _ in 0..64 * 4 {
+        app.update();
+    }
+    assert_eq!(app.world().resource::<ScoreCount>().0, 1);
+    assert_eq!(app.world().resource::<PenaltyCount>().0, 0);
+}
+
+#[test]
+fn dweeb_startled_early_in_rem_scribes_an_idea() {
+    let mut app = create_app();
+    spawn_desk(&mut app, Vec3::new(-5.0, 1.0, 0.0));
+    let (dweeb, _) = put_dweeb_to_sleep(&mut app);
+    // Like a dweeb that has just scribed its previous idea. The other needs are left as they are
+    app.world_mut()
+        .get_mut::<DweebNeeds>(dweeb)
+        .unwrap()
+        .inspiration = 0.0;
+
+    let mut reached_rem = false;
+    for _ in 0..64 * 20 {
+        app.update();
+        if app
+            .world()
+            .get::<DweebBehaviorSleep>(dweeb)
+            .is_some_and(|sleep| sleep.stage_is_rem)
+        {
+            reached_rem = true;
+            break;
+        }
+    }
+    assert!(reached_rem);
+    hit_dweeb(&mut app, dweeb, true);
+
+    let history = run_until_behavior(&mut app, dweeb, "Scribe", 64 * 20);
+    assert_chain(&history, &["Startled", "WalkToDesk", "Scribe"]);
+}
+
+#[test]
+fn dweeb_startled_from_non_rem_goes_back_to_bed() {
+    let mut app = create_app();
+    spawn_desk(&mut app, Vec3::new(-5.0, 1.0, 0.0));
+    let (dweeb, _) = put_dweeb_to_sleep(&mut app);
+    startle_dweeb(&mut app, dweeb, false);
+
+    let history = run_until_behavior(&mut app, dweeb, "WalkToBed", 64 * 20);
+    assert_chain(&history, &["Startled", "Idle", "WalkToBed"]);
+    assert_eq!(app.world().resource::<ScoreCount>().0, 0);
+    assert_eq!(app.world().resource::<PenaltyCount>().0, 1);
+    assert!(0.0 < app.world().get::<DweebNeeds>(dweeb).unwrap().grumpiness);
+}
+
+#[test]
+fn only_one_dweeb_walks_to_a_single_bed() {
+    let mut app = create_app();
+    let dweebs = [
+        spawn_dweeb(&mut app, Vec3::new(0.0, 2.0, 1.0)),
+        spawn_dweeb(&mut app, Vec3::new(0.0, 2.0, -1.0)),
+    ];
+    let bed = spawn_bed(&mut app, Vec3::new(5.0, 1.3, 0.0), 1);
+
+    let mut reached_bed = false;
+    let mut both_heading_to_bed_for = 0;
+    for _ in 0..64 * 20 {
+        app.update();
+        let heading_to_bed = dweebs
+            .iter()
+            .filter(|dweeb| {
+                matches!(
+                    behavior_of(&app, **dweeb),
+                    Some("WalkToBed" | "JumpOnBed" | "Sleep")
+                )
+            })
+            .count();
+        // Both dweebs may pick the bed on the same tick, but the one that did not get the
+        // reservation must give it up on the next one
+        if heading_to_bed == 2 {
+            both_heading_to_bed_for += 1;
+        } else {
+            both_heading_to_bed_for = 0;
+        }
+        assert!(both_heading_to_bed_for <= 2);
+        let reserved_by = dweebs
+            .iter()
+            .filter(|dweeb| {
+                app.world()
+                    .get::<Reservations>(bed)
+                    .unwrap()
+                    .is_held_by(**dweeb)
+            })
+            .count();
+        assert!(reserved_by <= 1);
+        if dweebs
+            .iter()
+            .any(|dweeb| behavior_of(&app, *dweeb) == Some("Sleep"))
+        {
+            reached_bed = true;
+            break;
+        }
+    }
+    assert!(reached_bed);
+    assert!(dweebs
+        .iter()
+        .any(|dweeb| behavior_of(&app, *dweeb) != Some("Sleep")));
+}
+
+#[test]
+fn dweeb_walks_around_a_wall_to_the_bed() {
+    let mut app = create_app();
+    let dweeb = spawn_dweeb(&mut app, Vec3::new(0.0, 2.0, 0.0));
+    spawn_wall(&mut app, Vec3::new(3.5, 2.0, 0.0), Vec3::new(0.5, 3.0, 8.0));
+    let bed = spawn_bed(&mut app, Vec3::new(7.0, 1.3, 0.0), 1);
+
+    let history = run_until_behavior(&mut app, dweeb, "Sleep", 64 * 30);
+    assert_chain(&history, &["WalkToBed", "JumpOnBed", "Sleep"]);
+    assert_eq!(
+        app.world()
+            .get::<DweebBehaviorSleep>(dweeb)
+            .unwrap()
+            .bed_entity,
+        bed
+    );
+}
+
+#[test]
+fn sleep_does_not_progress_while_the_gameplay_clock_is_paused() {
+    let mut app = create_app();
+    let (dweeb, _) = put_dweeb_to_sleep(&mut app);
+    let stage_progress = |app: &App| {
+        app.world()
+            .get::<DweebBehaviorSleep>(dweeb)
+            .unwrap()
+            .stage_progress
+    };
+
+    let rng_state = |app: &App| app.world().get::<RngComponent>(dweeb).unwrap().clone();
+
+    app.world_mut()
+        .resource_mut::<GameplayClock>()
+        .set_paused(true);
+    let progress_before = stage_progress(&app);
+    let rng_before = rng_state(&app);
+    for _ in 0..64 {
+        app.update();
+    }
+    assert_eq!(stage_progress(&app), progress_before);
+    // Otherwise pausing would make replays go out of sync
+    assert!(rng_state(&app) == rng_before);
+
+    app.world_mut()
+        .resource_mut::<GameplayClock>()
+        .set_paused(false);
+    for _ in 0..64 {
+        app.update();
+    }
+    assert_ne!(stage_progress(&app), progress_before);
+}
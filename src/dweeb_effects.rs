@@ -1,23 +1,45 @@
 use std::{
+    f32::consts::{FRAC_PI_2, TAU},
     mem::{self, Discriminant},
-    time::Duration,
 };
 
 use bevy::prelude::*;
 use bevy_turborand::prelude::*;
 
-use crate::{dweeb::Dweeb, During};
+use crate::{
+    dweeb::Dweeb,
+    gameplay_time::GameplayTime,
+    particles::{spawn_particle, Particle, ParticleVisual},
+    score::IncreaseScore,
+    During,
+};
+
+/// Where the effects come out of, relative to the dweeb.
+const HEAD_OFFSET: Vec3 = Vec3::Y;
+/// Seconds between Zs.
+const NON_REM_Z_INTERVAL: f32 = 1.0;
+/// REM sleep is the one the player wants to interrupt, so it should be easy to spot.
+const REM_Z_INTERVAL: f32 = 0.25;
+/// Seconds between question marks.
+const CONFUSION_INTERVAL: f32 = 0.2;
+const LIGHTBULB_POP_SPARKS: usize = 16;
 
 pub struct DweebEffectsPlugin;
 
 impl Plugin for DweebEffectsPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<EffectVisuals>();
         app.observe(add_effect_to_dweeb);
         app.add_systems(
             Update,
             (
                 handle_effect_discriminant_changes,
-                handle_effect_particles.in_set(During::Gameplay),
+                (
+                    emit_effect_particles,
+                    pop_lightbulbs,
+                    follow_effect_indicators,
+                )
+                    .in_set(During::Gameplay),
             ),
         );
     }
@@ -27,6 +49,7 @@ fn add_effect_to_dweeb(trigger: Trigger<OnInsert, Dweeb>, mut commands: Commands
     commands.entity(trigger.entity()).insert((
         DweebEffect::None,
         OldEffect(mem::discriminant(&DweebEffect::None)),
+        EffectEmitter { cooldown: 0.0 },
     ));
 }
 
@@ -41,123 +64,196 @@ pub enum DweebEffect {
 #[derive(Component)]
 struct OldEffect(Discriminant<DweebEffect>);
 
+#[derive(Component)]
+struct EffectEmitter {
+    /// Seconds until the next particle.
+    cooldown: f32,
+}
+
+/// An effect that stays above the dweeb's head for as long as the effect lasts, rather than being
+/// emitted as particles.
+#[derive(Component)]
+struct EffectIndicator {
+    owner: Entity,
+}
+
+#[derive(Resource)]
+struct EffectVisuals {
+    z: ParticleVisual,
+    question_mark: ParticleVisual,
+    spark: ParticleVisual,
+}
+
+impl FromWorld for EffectVisuals {
+    fn from_world(world: &mut World) -> Self {
+        // The text meshes are modeled lying down with their corner at the origin. The original
+        // scenes fix that with the node transform, which is lost when only loading the mesh.
+        let text_transform = |x_offset: f32| {
+            Transform::from_xyz(x_offset, -0.3, 0.0).with_rotation(Quat::from_rotation_x(FRAC_PI_2))
+        };
+        let asset_server = world.resource::<AssetServer>();
+        let z_mesh = asset_server.load("Z.glb#Mesh0/Primitive0");
+        let question_mark_mesh = asset_server.load("Confusion.glb#Mesh0/Primitive0");
+        let spark_mesh = world.resource_mut::<Assets<Mesh>>().add(Sphere::new(1.0));
+        Self {
+            z: ParticleVisual {
+                mesh: z_mesh,
+                mesh_transform: text_transform(-0.3),
+                color: Color::linear_rgb(0.12, 0.12, 0.12),
+            },
+            question_mark: ParticleVisual {
+                mesh: question_mark_mesh,
+                mesh_transform: text_transform(-0.16),
+                color: Color::linear_rgb(0.21, 0.2, 0.024),
+            },
+            spark: ParticleVisual {
+                mesh: spark_mesh,
+                mesh_transform: Transform::IDENTITY,
+                color: Color::linear_rgb(0.69, 0.52, 0.0015),
+            },
+        }
+    }
+}
+
 fn handle_effect_discriminant_changes(
-    mut query: Query<(Entity, &DweebEffect, &mut OldEffect)>,
-    particles_query: Query<(Entity, &EffectParticle)>,
+    mut query: Query<(Entity, &DweebEffect, &mut OldEffect, &mut EffectEmitter)>,
+    indicators_query: Query<(Entity, &EffectIndicator)>,
     mut commands: Commands,
-    mut global_rng: ResMut<GlobalRng>,
     asset_server: Res<AssetServer>,
 ) {
-    for (entity, effect, mut old) in query.iter_mut() {
+    for (entity, effect, mut old, mut emitter) in query.iter_mut() {
         let new_discriminant = mem::discriminant(effect);
         if new_discriminant == old.0 {
             continue;
         }
         old.0 = new_discriminant;
+        emitter.cooldown = 0.0;
 
-        for (particle_entity, particle) in particles_query.iter() {
-            if particle.owner == entity {
-                commands.entity(particle_entity).despawn_recursive();
+        for (indicator_entity, indicator) in indicators_query.iter() {
+            if indicator.owner == entity {
+                commands.entity(indicator_entity).despawn_recursive();
             }
         }
 
-        match effect {
-            DweebEffect::None => {}
-            DweebEffect::Zs { .. } => {
-                commands.spawn((
-                    SceneBundle {
-                        scene: asset_server.load("Z.glb#Scene0"),
-                        transform: Transform::from_xyz(0.0, -4.0, 0.0),
-                        ..Default::default()
-                    },
-                    EffectParticle {
-                        owner: entity,
-                        timer: {
-                            let mut timer = Timer::new(
-                                Duration::from_secs_f32(2.0 + 0.5 * global_rng.f32_normalized()),
-                                TimerMode::Repeating,
-                            );
-                            timer.set_elapsed(timer.duration().mul_f32(global_rng.f32()));
-                            timer
-                        },
-                    },
-                ));
-            }
-            DweebEffect::Confusion => {
-                commands.spawn((
-                    SceneBundle {
-                        scene: asset_server.load("Confusion.glb#Scene0"),
-                        transform: Transform::from_xyz(0.0, -4.0, 0.0),
-                        ..Default::default()
-                    },
-                    EffectParticle {
-                        owner: entity,
-                        timer: Default::default(),
-                    },
-                ));
-            }
-            DweebEffect::Lightbulb => {
-                commands.spawn((
-                    SceneBundle {
-                        scene: asset_server.load("Lightbulb.glb#Scene0"),
-                        transform: Transform::from_xyz(0.0, -4.0, 0.0),
-                        ..Default::default()
-                    },
-                    EffectParticle {
-                        owner: entity,
-                        timer: Default::default(),
-                    },
-                ));
-            }
+        if let DweebEffect::Lightbulb = effect {
+            commands.spawn((
+                SceneBundle {
+                    scene: asset_server.load("Lightbulb.glb#Scene0"),
+                    transform: Transform::from_xyz(0.0, -4.0, 0.0),
+                    ..Default::default()
+                },
+                EffectIndicator { owner: entity },
+            ));
         }
     }
 }
 
-#[derive(Component)]
-struct EffectParticle {
-    owner: Entity,
-    timer: Timer,
+fn emit_effect_particles(
+    time: GameplayTime,
+    mut query: Query<(&DweebEffect, &mut EffectEmitter, &GlobalTransform)>,
+    visuals: Res<EffectVisuals>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+    mut global_rng: ResMut<GlobalRng>,
+) {
+    for (effect, mut emitter, transform) in query.iter_mut() {
+        let interval = match effect {
+            DweebEffect::None | DweebEffect::Lightbulb => continue,
+            DweebEffect::Zs { is_rem: false } => NON_REM_Z_INTERVAL,
+            DweebEffect::Zs { is_rem: true } => REM_Z_INTERVAL,
+            DweebEffect::Confusion => CONFUSION_INTERVAL,
+        };
+        emitter.cooldown -= time.delta_seconds();
+        while emitter.cooldown <= 0.0 {
+            emitter.cooldown += interval * (0.75 + 0.5 * global_rng.f32());
+            let head = transform.translation() + HEAD_OFFSET;
+            match effect {
+                DweebEffect::None | DweebEffect::Lightbulb => {}
+                DweebEffect::Zs { .. } => {
+                    // Rising, drifting sideways a bit, and growing while fading out
+                    spawn_particle(
+                        &mut commands,
+                        &mut materials,
+                        &visuals.z,
+                        head + 0.2 * global_rng.f32_normalized() * Vec3::X,
+                        Particle {
+                            velocity: Vec3::new(0.3 * global_rng.f32_normalized(), 0.8, 0.0),
+                            scale: [0.4, 1.2],
+                            alpha: [1.0, 0.0],
+                            ..Particle::with_lifetime(2.5)
+                        },
+                    );
+                }
+                DweebEffect::Confusion => {
+                    // Spiraling up around the head
+                    let angle = TAU * global_rng.f32();
+                    spawn_particle(
+                        &mut commands,
+                        &mut materials,
+                        &visuals.question_mark,
+                        head + Quat::from_rotation_y(angle) * (0.6 * Vec3::X),
+                        Particle {
+                            velocity: 0.4 * Vec3::Y,
+                            swirl_speed: 4.0,
+                            swirl_center: head,
+                            scale: [0.8, 0.3],
+                            alpha: [1.0, 0.0],
+                            ..Particle::with_lifetime(1.5)
+                        },
+                    );
+                }
+            }
+        }
+    }
 }
 
-fn handle_effect_particles(
-    time: Res<Time>,
-    mut query: Query<(Entity, &mut EffectParticle, &mut Transform)>,
-    owners_query: Query<(&DweebEffect, &GlobalTransform)>,
+fn pop_lightbulbs(
+    mut reader: EventReader<IncreaseScore>,
+    dweebs_query: Query<&GlobalTransform>,
+    visuals: Res<EffectVisuals>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
     mut commands: Commands,
     mut global_rng: ResMut<GlobalRng>,
 ) {
-    for (particle_entity, mut particle, mut particle_transform) in query.iter_mut() {
-        let Ok((effect, owner_transform)) = owners_query.get(particle.owner) else {
-            commands.entity(particle_entity).despawn_recursive();
+    for IncreaseScore { dweeb, .. } in reader.read() {
+        let Ok(transform) = dweebs_query.get(*dweeb) else {
             continue;
         };
-        match effect {
-            DweebEffect::None => {
-                commands.entity(particle_entity).despawn_recursive();
-            }
-            DweebEffect::Zs { is_rem } => {
-                let time_multiplier = if *is_rem {
-                    10.0 + 2.0 * global_rng.f32_normalized()
-                } else {
-                    1.0
-                };
-                let timer_progress = particle
-                    .timer
-                    .tick(time.delta().mul_f32(time_multiplier))
-                    .elapsed_secs()
-                    / particle.timer.duration().as_secs_f32();
-                let animation_progress = 2.0
-                    * if timer_progress < 0.5 {
-                        timer_progress
-                    } else {
-                        1.0 - timer_progress
-                    };
-                particle_transform.translation =
-                    owner_transform.translation() + (1.0 + 0.2 * animation_progress) * Vec3::Y;
-            }
-            DweebEffect::Confusion | DweebEffect::Lightbulb => {
-                particle_transform.translation = owner_transform.translation() + 1.0 * Vec3::Y;
-            }
+        let head = transform.translation() + HEAD_OFFSET;
+        for _ in 0..LIGHTBULB_POP_SPARKS {
+            let direction = Vec3::new(
+                global_rng.f32_normalized(),
+                0.5 + global_rng.f32(),
+                global_rng.f32_normalized(),
+            )
+            .normalize_or_zero();
+            spawn_particle(
+                &mut commands,
+                &mut materials,
+                &visuals.spark,
+                head,
+                Particle {
+                    velocity: (3.0 + 2.0 * global_rng.f32()) * direction,
+                    acceleration: -9.8 * Vec3::Y,
+                    scale: [0.15, 0.0],
+                    alpha: [1.0, 0.0],
+                    ..Particle::with_lifetime(0.8)
+                },
+            );
         }
     }
 }
+
+fn follow_effect_indicators(
+    mut query: Query<(Entity, &EffectIndicator, &mut Transform)>,
+    owners_query: Query<&GlobalTransform>,
+    mut commands: Commands,
+) {
+    for (indicator_entity, indicator, mut indicator_transform) in query.iter_mut() {
+        let Ok(owner_transform) = owners_query.get(indicator.owner) else {
+            commands.entity(indicator_entity).despawn_recursive();
+            continue;
+        };
+        indicator_transform.translation = owner_transform.translation() + HEAD_OFFSET;
+    }
+}
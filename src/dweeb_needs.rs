@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use bevy_turborand::prelude::*;
+
+use crate::{dweeb_behavior::describe_behavior, DisplayUi};
+
+pub struct DweebNeedsPlugin;
+
+impl Plugin for DweebNeedsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ShowDweebNeeds>();
+        app.add_systems(
+            Update,
+            (
+                toggle_needs_overlay,
+                display_needs_overlay.run_if(|show: Res<ShowDweebNeeds>| show.0),
+            )
+                .in_set(DisplayUi),
+        );
+    }
+}
+
+/// What a dweeb wants to do next. All the values are between 0 and 1.
+#[derive(Component, Debug, Clone)]
+pub struct DweebNeeds {
+    /// Makes the dweeb want to go to bed. Rises while awake and drops while sleeping.
+    pub fatigue: f32,
+    /// Makes the dweeb want to go to a desk. Rises during REM sleep and is spent on scribing.
+    pub inspiration: f32,
+    /// Makes the dweeb wake up on its own. Rises while standing around and drops while moving or
+    /// sleeping.
+    pub restlessness: f32,
+    /// Makes it harder to put the dweeb back to sleep. Rises when woken from non-REM sleep and
+    /// slowly fades.
+    pub grumpiness: f32,
+}
+
+impl DweebNeeds {
+    pub fn random(rng: &mut RngComponent) -> Self {
+        Self {
+            fatigue: 0.4 + 0.6 * rng.f32(),
+            inspiration: 0.3 * rng.f32(),
+            restlessness: 0.3 * rng.f32(),
+            grumpiness: 0.0,
+        }
+    }
+
+    /// Apply rates of change (per second) to the needs.
+    pub fn change(&mut self, delta_secs: f32, fatigue: f32, inspiration: f32, restlessness: f32) {
+        self.fatigue = (self.fatigue + delta_secs * fatigue).clamp(0.0, 1.0);
+        self.inspiration = (self.inspiration + delta_secs * inspiration).clamp(0.0, 1.0);
+        self.restlessness = (self.restlessness + delta_secs * restlessness).clamp(0.0, 1.0);
+    }
+}
+
+#[derive(Resource, Default)]
+struct ShowDweebNeeds(bool);
+
+fn toggle_needs_overlay(keyboard: Res<ButtonInput<KeyCode>>, mut show: ResMut<ShowDweebNeeds>) {
+    if keyboard.just_pressed(KeyCode::F3) {
+        show.0 = !show.0;
+    }
+}
+
+fn display_needs_overlay(
+    mut egui_contexts: EguiContexts,
+    query: Query<EntityRef, With<DweebNeeds>>,
+) {
+    let mut dweebs = query.iter().collect::<Vec<_>>();
+    dweebs.sort_by_key(|dweeb| dweeb.id());
+    egui::Window::new("Dweeb Needs")
+        .anchor(egui::Align2::RIGHT_TOP, [0.0, 0.0])
+        .resizable(false)
+        .show(egui_contexts.ctx_mut(), |ui| {
+            egui::Grid::new("dweeb-needs-grid")
+                .striped(true)
+                .show(ui, |ui| {
+                    ui.label("Dweeb");
+                    ui.label("Behavior");
+                    ui.label("Fatigue");
+                    ui.label("Inspiration");
+                    ui.label("Restlessness");
+                    ui.label("Grumpiness");
+                    ui.end_row();
+                    for dweeb in dweebs {
+                        let Some(needs) = dweeb.get::<DweebNeeds>() else {
+                            continue;
+                        };
+                        ui.label(dweeb.id().to_string());
+                        ui.label(
+                            describe_behavior(&dweeb)
+                                .map(|description| description.name)
+                                .unwrap_or_default(),
+                        );
+                        for value in [
+                            needs.fatigue,
+                            needs.inspiration,
+                            needs.restlessness,
+                            needs.grumpiness,
+                        ] {
+                            ui.add(
+                                egui::ProgressBar::new(value)
+                                    .desired_width(80.0)
+                                    .text(format!("{value:.2}")),
+                            );
+                        }
+                        ui.end_row();
+                    }
+                });
+        });
+}
@@ -0,0 +1,69 @@
+use bevy::prelude::*;
+
+use crate::{
+    dweeb::Dweeb,
+    dweeb_behavior::is_startled,
+    level_rules::LevelRules,
+    player::IsPlayer,
+    score::{GameData, GameOverReason},
+    AppState, During,
+};
+
+pub struct FailurePlugin;
+
+impl Plugin for FailurePlugin {
+    fn build(&self, app: &mut App) {
+        // In `FixedUpdate`, so that replays fail on the same tick they did when recorded
+        app.add_systems(
+            FixedUpdate,
+            (check_fallen, check_too_many_startled).in_set(During::Gameplay),
+        );
+    }
+}
+
+fn fail(reason: GameOverReason, game_data: &mut GameData, next_state: &mut NextState<AppState>) {
+    game_data.set_game_over_reason(reason);
+    next_state.set(AppState::GameOver);
+}
+
+fn check_fallen(
+    players_query: Query<&GlobalTransform, With<IsPlayer>>,
+    dweebs_query: Query<&GlobalTransform, With<Dweeb>>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    mut game_data: ResMut<GameData>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    let is_below_kill_plane =
+        |transform: &GlobalTransform| transform.translation().y < level_rules.kill_plane_height;
+    if players_query.iter().any(is_below_kill_plane) {
+        fail(GameOverReason::PlayerFell, &mut game_data, &mut next_state);
+    } else if level_rules.fail_on_dweeb_knocked_off && dweebs_query.iter().any(is_below_kill_plane)
+    {
+        fail(
+            GameOverReason::DweebKnockedOff,
+            &mut game_data,
+            &mut next_state,
+        );
+    }
+}
+
+fn check_too_many_startled(
+    dweebs_query: Query<EntityRef, With<Dweeb>>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    mut game_data: ResMut<GameData>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    if level_rules.max_startled_dweebs == 0 {
+        return;
+    }
+    let startled_count = dweebs_query.iter().filter(is_startled).count();
+    if level_rules.max_startled_dweebs < startled_count {
+        fail(
+            GameOverReason::TooManyStartled,
+            &mut game_data,
+            &mut next_state,
+        );
+    }
+}
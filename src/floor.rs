@@ -0,0 +1,102 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_yoleck::{prelude::*, vpeol_3d::Vpeol3dPosition};
+use ordered_float::OrderedFloat;
+use serde::{Deserialize, Serialize};
+
+use crate::navigation::Ground;
+
+/// Floors are always this thick. Their collider is centered on their position, so their top surface
+/// is half of this above it.
+pub const FLOOR_THICKNESS: f32 = 1.0;
+
+pub struct FloorPlugin;
+
+impl Plugin for FloorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("Floor")
+                .with::<Vpeol3dPosition>()
+                .with::<FloorShape>()
+                .insert_on_init(|| (Floor, Ground))
+        });
+        app.add_yoleck_edit_system(edit_floor_shape);
+        app.add_systems(YoleckSchedule::Populate, populate_floor);
+    }
+}
+
+#[derive(Component)]
+pub struct Floor;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(default)]
+pub struct FloorShape {
+    /// Along the X and Z axes.
+    pub size: Vec2,
+    /// sRGB.
+    pub color: [f32; 3],
+}
+
+impl Default for FloorShape {
+    fn default() -> Self {
+        Self {
+            size: Vec2::new(20.0, 20.0),
+            // Slate gray
+            color: [0.439, 0.502, 0.565],
+        }
+    }
+}
+
+/// The height of the top surface of the floor under `position`. When floors at different heights
+/// overlap there, picks the one closest to `position`.
+pub fn floor_top_under<'a>(
+    position: Vec3,
+    floors: impl IntoIterator<Item = (&'a GlobalTransform, &'a FloorShape)>,
+) -> Option<f32> {
+    floors
+        .into_iter()
+        .filter(|(transform, floor_shape)| {
+            let offset = position.xz() - transform.translation().xz();
+            offset.abs().cmple(0.5 * floor_shape.size).all()
+        })
+        .map(|(transform, _)| transform.translation().y + 0.5 * FLOOR_THICKNESS)
+        .min_by_key(|top| OrderedFloat((top - position.y).abs()))
+}
+
+fn edit_floor_shape(
+    mut ui: ResMut<YoleckUi>,
+    mut edit: YoleckEdit<(&mut FloorShape, &mut Vpeol3dPosition)>,
+) {
+    let Ok((mut floor_shape, mut position)) = edit.get_single_mut() else {
+        return;
+    };
+    ui.add(egui::Slider::new(&mut position.0.y, -20.0..=20.0).text("Elevation"));
+    ui.add(egui::Slider::new(&mut floor_shape.size.x, 1.0..=200.0).text("Width"));
+    ui.add(egui::Slider::new(&mut floor_shape.size.y, 1.0..=200.0).text("Depth"));
+    ui.horizontal(|ui| {
+        ui.label("Color");
+        ui.color_edit_button_rgb(&mut floor_shape.color);
+    });
+}
+
+fn populate_floor(
+    mut pupulate: YoleckPopulate<&FloorShape, With<Floor>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    pupulate.populate(|ctx, mut cmd, floor_shape| {
+        if ctx.is_first_time() {
+            cmd.insert(PbrBundle::default());
+            cmd.insert(RigidBody::Static);
+        }
+        // The shape can change while editing, so these are not only inserted the first time
+        let size = Vec3::new(floor_shape.size.x, FLOOR_THICKNESS, floor_shape.size.y);
+        cmd.insert(meshes.add(Mesh::from(bevy::math::prelude::Cuboid {
+            half_size: 0.5 * size,
+        })));
+        let [red, green, blue] = floor_shape.color;
+        cmd.insert(materials.add(StandardMaterial::from_color(Color::srgb(red, green, blue))));
+        cmd.insert(Collider::cuboid(size.x, size.y, size.z));
+    });
+}
@@ -0,0 +1,126 @@
+//! Holding the focus button slows down the gameplay clock, so that the player can pick the right
+//! dweeb to dash at. The dash target is shown by the aim marker while the button is held.
+
+use avian3d::schedule::{Physics, PhysicsTime};
+use bevy::prelude::*;
+use bevy_egui::{egui, EguiContexts};
+use leafwing_input_manager::prelude::*;
+
+use crate::{
+    gameplay_time::GameplayClock, player::IsPlayer, player_controls::PlayerAction, AppState,
+    DisplayUi, During,
+};
+
+const FOCUS_TIME_SCALE: f32 = 0.25;
+/// In unscaled seconds, not in gameplay seconds.
+const FULL_METER_DRAIN_SECS: f32 = 3.0;
+const EMPTY_METER_RECHARGE_SECS: f32 = 15.0;
+/// So that holding the button with an empty meter would not flicker the focus on and off.
+const MIN_CHARGE_TO_START: f32 = 0.2;
+
+pub struct FocusPlugin;
+
+impl Plugin for FocusPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<FocusMeter>();
+        // Changes the speed of the physics, so it must be as deterministic as the physics
+        app.add_systems(FixedUpdate, update_focus.in_set(During::Gameplay));
+        app.add_systems(OnExit(AppState::Game), stop_focus);
+        app.add_systems(OnEnter(AppState::LoadLevel), refill_focus_meter);
+        app.add_systems(
+            Update,
+            display_focus_meter
+                .in_set(During::Gameplay)
+                .in_set(DisplayUi),
+        );
+    }
+}
+
+#[derive(Resource)]
+struct FocusMeter {
+    /// Between 0 and 1.
+    charge: f32,
+    is_focusing: bool,
+}
+
+impl Default for FocusMeter {
+    fn default() -> Self {
+        Self {
+            charge: 1.0,
+            is_focusing: false,
+        }
+    }
+}
+
+/// Both the physics and the gameplay clock, on the same tick, so that the dweebs and the physics
+/// never run at different speeds.
+fn set_gameplay_speed(
+    speed: f32,
+    gameplay_clock: &mut GameplayClock,
+    physics_time: &mut Time<Physics>,
+) {
+    gameplay_clock.set_speed(speed);
+    physics_time.set_relative_speed(speed);
+}
+
+fn update_focus(
+    time: Res<Time>,
+    players_query: Query<&ActionState<PlayerAction>, With<IsPlayer>>,
+    mut meter: ResMut<FocusMeter>,
+    mut gameplay_clock: ResMut<GameplayClock>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    let is_pressed = players_query
+        .iter()
+        .any(|input| input.pressed(&PlayerAction::Focus));
+    meter.is_focusing = is_pressed
+        && 0.0 < meter.charge
+        && (meter.is_focusing || MIN_CHARGE_TO_START <= meter.charge);
+
+    // `Time` is not affected by the gameplay clock, so the meter drains in unscaled time
+    let delta_secs = time.delta_seconds();
+    if meter.is_focusing {
+        meter.charge = (meter.charge - delta_secs / FULL_METER_DRAIN_SECS).max(0.0);
+        set_gameplay_speed(FOCUS_TIME_SCALE, &mut gameplay_clock, &mut physics_time);
+    } else {
+        meter.charge = (meter.charge + delta_secs / EMPTY_METER_RECHARGE_SECS).min(1.0);
+        set_gameplay_speed(1.0, &mut gameplay_clock, &mut physics_time);
+    }
+}
+
+fn stop_focus(
+    mut meter: ResMut<FocusMeter>,
+    mut gameplay_clock: ResMut<GameplayClock>,
+    mut physics_time: ResMut<Time<Physics>>,
+) {
+    meter.is_focusing = false;
+    set_gameplay_speed(1.0, &mut gameplay_clock, &mut physics_time);
+}
+
+fn refill_focus_meter(mut meter: ResMut<FocusMeter>) {
+    *meter = Default::default();
+}
+
+fn display_focus_meter(mut egui_contexts: EguiContexts, meter: Res<FocusMeter>) {
+    let panel =
+        egui::Area::new("display-focus".into()).anchor(egui::Align2::LEFT_BOTTOM, [0.0, 0.0]);
+    panel.show(egui_contexts.ctx_mut(), |ui| {
+        ui.add(
+            egui::ProgressBar::new(meter.charge)
+                .desired_width(150.0)
+                .fill(if meter.is_focusing {
+                    egui::Color32::LIGHT_GREEN
+                } else if meter.charge < MIN_CHARGE_TO_START {
+                    egui::Color32::GRAY
+                } else {
+                    egui::Color32::DARK_GREEN
+                })
+                .text(
+                    egui::RichText::new("Focus")
+                        .strong()
+                        .color(egui::Color32::WHITE)
+                        .size(24.0),
+                ),
+        );
+    });
+}
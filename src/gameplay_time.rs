@@ -0,0 +1,64 @@
+//! A clock for everything that happens during gameplay, so that it can be paused together with the
+//! physics and slowed down.
+
+use std::time::Duration;
+
+use bevy::{ecs::system::SystemParam, prelude::*};
+
+#[derive(Resource)]
+pub struct GameplayClock {
+    is_paused: bool,
+    speed: f32,
+}
+
+impl Default for GameplayClock {
+    fn default() -> Self {
+        Self {
+            is_paused: false,
+            speed: 1.0,
+        }
+    }
+}
+
+impl GameplayClock {
+    pub fn is_paused(&self) -> bool {
+        self.is_paused
+    }
+
+    pub fn set_paused(&mut self, is_paused: bool) {
+        self.is_paused = is_paused;
+    }
+
+    /// 1.0 is normal speed. Lower values are slow motion.
+    pub fn set_speed(&mut self, speed: f32) {
+        self.speed = speed;
+    }
+}
+
+/// A run condition for systems that must not run at all while the gameplay is paused - e.g. because
+/// they draw random numbers, which would make replays go out of sync.
+pub fn gameplay_is_running(clock: Res<GameplayClock>) -> bool {
+    !clock.is_paused
+}
+
+/// Like `Res<Time>`, but according to the [`GameplayClock`]. Works both in `Update` and in
+/// `FixedUpdate`.
+#[derive(SystemParam)]
+pub struct GameplayTime<'w> {
+    time: Res<'w, Time>,
+    clock: Res<'w, GameplayClock>,
+}
+
+impl GameplayTime<'_> {
+    pub fn delta(&self) -> Duration {
+        if self.clock.is_paused {
+            Duration::ZERO
+        } else {
+            self.time.delta().mul_f32(self.clock.speed)
+        }
+    }
+
+    pub fn delta_seconds(&self) -> f32 {
+        self.delta().as_secs_f32()
+    }
+}
@@ -0,0 +1,120 @@
+use bevy::{prelude::*, utils::HashMap};
+use serde::{Deserialize, Serialize};
+
+use crate::{loading::CurrentLevel, persistence, score::GameData, AppState};
+
+const MAX_ENTRIES_PER_LEVEL: usize = 5;
+
+pub struct HighScoresPlugin {
+    /// When `false` the leaderboard can still be displayed, but finished runs are not added to it.
+    pub record_scores: bool,
+}
+
+impl Plugin for HighScoresPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persistence::load::<HighScores>("high-scores").unwrap_or_default());
+        app.init_resource::<LastRunRank>();
+        if self.record_scores {
+            app.add_systems(OnEnter(AppState::LevelCompleted), record_high_score);
+            app.add_systems(OnEnter(AppState::GameOver), record_high_score);
+        }
+    }
+}
+
+#[derive(Resource, Default, Serialize, Deserialize)]
+pub struct HighScores {
+    /// Keyed by the level's filename.
+    levels: HashMap<String, Vec<HighScoreEntry>>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct HighScoreEntry {
+    /// In points, not in ideas.
+    pub score: usize,
+    /// Missing from entries saved before it was added.
+    #[serde(default)]
+    pub ideas_scribed: usize,
+    /// Seconds from the start of the level until it was completed. `None` if the level was not
+    /// completed.
+    pub completion_time: Option<f32>,
+    /// Seconds since the Unix epoch.
+    pub timestamp: u64,
+}
+
+impl HighScoreEntry {
+    fn is_better_than(&self, other: &Self) -> bool {
+        if self.score != other.score {
+            return other.score < self.score;
+        }
+        match (self.completion_time, other.completion_time) {
+            (Some(self_time), Some(other_time)) => self_time < other_time,
+            (Some(_), None) => true,
+            (None, _) => false,
+        }
+    }
+}
+
+impl HighScores {
+    pub fn leaderboard(&self, level: &str) -> &[HighScoreEntry] {
+        self.levels
+            .get(level)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+
+    pub fn best_time(&self, level: &str) -> Option<f32> {
+        self.leaderboard(level)
+            .iter()
+            .filter_map(|entry| entry.completion_time)
+            .min_by(f32::total_cmp)
+    }
+
+    /// Returns the rank (0 based) of the new entry, or `None` if it did not make it into the
+    /// leaderboard.
+    fn record(&mut self, level: &str, new_entry: HighScoreEntry) -> Option<usize> {
+        let entries = self.levels.entry(level.to_owned()).or_default();
+        let rank = entries
+            .iter()
+            .position(|entry| new_entry.is_better_than(entry))
+            .unwrap_or(entries.len());
+        if MAX_ENTRIES_PER_LEVEL <= rank {
+            return None;
+        }
+        entries.insert(rank, new_entry);
+        entries.truncate(MAX_ENTRIES_PER_LEVEL);
+        Some(rank)
+    }
+}
+
+/// Where the last finished run landed in its level's leaderboard.
+#[derive(Resource, Default)]
+pub struct LastRunRank(pub Option<usize>);
+
+impl LastRunRank {
+    pub fn is_new_best(&self) -> bool {
+        self.0 == Some(0)
+    }
+}
+
+fn record_high_score(
+    game_data: Res<GameData>,
+    current_level: Res<CurrentLevel>,
+    mut high_scores: ResMut<HighScores>,
+    mut last_run_rank: ResMut<LastRunRank>,
+) {
+    let completion_time = game_data
+        .is_target_reached()
+        .then(|| game_data.elapsed_secs());
+    last_run_rank.0 = high_scores.record(
+        &current_level.0,
+        HighScoreEntry {
+            score: game_data.score(),
+            ideas_scribed: game_data.ideas_scribed(),
+            completion_time,
+            timestamp: persistence::now_timestamp(),
+        },
+    );
+    if last_run_rank.0.is_some() {
+        persistence::save("high-scores", high_scores.as_ref());
+    }
+}
@@ -0,0 +1,111 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_yoleck::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct LevelRulesPlugin;
+
+impl Plugin for LevelRulesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_entity_type(YoleckEntityType::new("LevelRules").with::<LevelRules>());
+        app.add_yoleck_edit_system(edit_level_rules);
+    }
+}
+
+/// Level-wide tuning. Levels without a `LevelRules` entity use the default values.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(default)]
+pub struct LevelRules {
+    /// In seconds.
+    pub time_limit: f32,
+    pub target_score: usize,
+    pub dweeb_speed: f32,
+    /// Sleep stage progress per second during non-REM sleep.
+    pub non_rem_rate: f32,
+    /// Sleep stage progress per second during REM sleep.
+    pub rem_rate: f32,
+    /// Seconds taken off the clock when the player wakes a dweeb from non-REM sleep.
+    pub non_rem_wake_penalty: f32,
+    /// The player (or a dweeb) falling below this height ends the game.
+    pub kill_plane_height: f32,
+    pub fail_on_dweeb_knocked_off: bool,
+    /// Having more dweebs startled at once ends the game. Zero means there is no limit.
+    pub max_startled_dweebs: usize,
+    /// How close the player can zoom the camera in.
+    pub min_camera_distance: f32,
+    /// How far the player can zoom the camera out.
+    pub max_camera_distance: f32,
+}
+
+impl Default for LevelRules {
+    fn default() -> Self {
+        Self {
+            time_limit: 60.0,
+            target_score: 10,
+            dweeb_speed: 2.5,
+            non_rem_rate: 0.15,
+            rem_rate: 0.3,
+            non_rem_wake_penalty: 5.0,
+            kill_plane_height: -10.0,
+            fail_on_dweeb_knocked_off: true,
+            max_startled_dweebs: 0,
+            min_camera_distance: 15.0,
+            max_camera_distance: 50.0,
+        }
+    }
+}
+
+impl LevelRules {
+    /// A level should have at most one `LevelRules`. If it has more, the one with the lowest
+    /// `Entity` is used, so that the choice does not depend on the query's iteration order.
+    pub fn from_query(query: &Query<(Entity, &LevelRules)>) -> Self {
+        if 1 < query.iter().len() {
+            warn_once!(
+                "The level has more than one LevelRules - using the one with the lowest entity"
+            );
+        }
+        query
+            .iter()
+            .min_by_key(|(entity, _)| *entity)
+            .map(|(_, level_rules)| *level_rules)
+            .unwrap_or_default()
+    }
+}
+
+fn edit_level_rules(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut LevelRules>) {
+    let Ok(mut level_rules) = edit.get_single_mut() else {
+        return;
+    };
+    ui.add(egui::Slider::new(&mut level_rules.time_limit, 10.0..=300.0).text("Time Limit"));
+    ui.add(egui::Slider::new(&mut level_rules.target_score, 1..=100).text("Target Score"));
+    ui.add(egui::Slider::new(&mut level_rules.dweeb_speed, 0.5..=10.0).text("Dweeb Speed"));
+    ui.add(egui::Slider::new(&mut level_rules.non_rem_rate, 0.01..=1.0).text("Non-REM Rate"));
+    ui.add(egui::Slider::new(&mut level_rules.rem_rate, 0.01..=1.0).text("REM Rate"));
+    ui.add(
+        egui::Slider::new(&mut level_rules.non_rem_wake_penalty, 0.0..=30.0)
+            .text("Non-REM Wake Penalty"),
+    );
+    ui.add(
+        egui::Slider::new(&mut level_rules.kill_plane_height, -50.0..=0.0)
+            .text("Kill Plane Height"),
+    );
+    ui.checkbox(
+        &mut level_rules.fail_on_dweeb_knocked_off,
+        "Fail on Dweeb Knocked Off",
+    );
+    ui.add(
+        egui::Slider::new(&mut level_rules.max_startled_dweebs, 0..=20)
+            .text("Max Startled Dweebs (0 for no limit)"),
+    );
+    ui.add(
+        egui::Slider::new(&mut level_rules.min_camera_distance, 5.0..=100.0)
+            .text("Min Camera Distance"),
+    );
+    ui.add(
+        egui::Slider::new(&mut level_rules.max_camera_distance, 5.0..=100.0)
+            .text("Max Camera Distance"),
+    );
+    level_rules.max_camera_distance = level_rules
+        .max_camera_distance
+        .max(level_rules.min_camera_distance);
+}
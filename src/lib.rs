@@ -1,92 +1,164 @@
-use arena::ArenaPlugin;
+use aim_marker::AimMarkerPlugin;
 use bed::BedPlugin;
-use bevy::prelude::*;
+use bevy::{ecs::schedule::ScheduleLabel, prelude::*};
 use bevy_yoleck::prelude::*;
 use camera::SwiftDreamsAreMadeForDweebsCameraPlugin;
 use desk::DeskPlugin;
 use dweeb::DweebPlugin;
 use dweeb_behavior::DweebBehaviorPlugin;
 use dweeb_effects::DweebEffectsPlugin;
-use loading::LoadingPlugin;
+use dweeb_needs::DweebNeedsPlugin;
+use failure::FailurePlugin;
+use floor::FloorPlugin;
+use focus::FocusPlugin;
+use gameplay_time::GameplayClock;
+use high_scores::HighScoresPlugin;
+use level_rules::LevelRulesPlugin;
+use loading::{CurrentLevel, LoadingPlugin};
 use menu::MenuPlugin;
+use navigation::NavigationPlugin;
+use particles::ParticlesPlugin;
 use player::PlayerPlugin;
+use player_attack::PlayerAttackPlugin;
 use player_controls::PlayerControlsPlugin;
+use replay::ReplayPlugin;
+use reservation::ReservationPlugin;
 use score::ScorePlugin;
+use settings::SettingsPlugin;
+use simulation::SimulationPlugin;
+use wall::WallPlugin;
 
-mod arena;
+mod aim_marker;
 mod bed;
 mod camera;
 mod desk;
 mod dweeb;
 mod dweeb_behavior;
 mod dweeb_effects;
+mod dweeb_needs;
+mod failure;
+mod floor;
+mod focus;
+mod gameplay_time;
+mod high_scores;
+mod level_rules;
 mod loading;
 mod menu;
+mod navigation;
+mod particles;
+mod persistence;
 mod player;
+mod player_attack;
 mod player_controls;
+mod replay;
+mod reservation;
 mod score;
+mod settings;
+mod simulation;
 mod util;
+mod wall;
+
+pub use replay::{Replay, ReplayMode};
 
 pub struct SwiftDreamsAreMadeForDweebsPlugin {
     pub is_editor: bool,
     pub start_at_level: Option<String>,
+    pub replay: Option<ReplayMode>,
+    /// Run the level headless for this many `FixedUpdate` ticks and print statistics.
+    pub simulate_ticks: Option<u64>,
 }
 
 impl Plugin for SwiftDreamsAreMadeForDweebsPlugin {
     fn build(&self, app: &mut App) {
-        app.configure_sets(
-            Update,
-            (
-                During::Menu.run_if(|state: Res<State<AppState>>| state.is_menu()),
-                During::Gameplay.run_if(in_state(AppState::Game)),
-            ),
-        );
+        for schedule in [
+            Update.intern(),
+            FixedUpdate.intern(),
+            FixedPostUpdate.intern(),
+        ] {
+            app.configure_sets(
+                schedule,
+                (
+                    During::Menu.run_if(|state: Res<State<AppState>>| state.is_menu()),
+                    During::Gameplay.run_if(in_state(AppState::Game)),
+                ),
+            );
+        }
+        // Headless simulations don't have egui
+        let has_ui = self.simulate_ticks.is_none();
+        app.configure_sets(Update, DisplayUi.run_if(move || has_ui));
         app.init_state::<AppState>();
-        app.add_plugins(SwiftDreamsAreMadeForDweebsCameraPlugin);
+        app.init_resource::<GameplayClock>();
+        if self.simulate_ticks.is_none() {
+            app.add_plugins(SwiftDreamsAreMadeForDweebsCameraPlugin);
+            app.add_plugins(AimMarkerPlugin);
+        }
         if self.is_editor {
             app.add_plugins(YoleckSyncWithEditorState {
                 when_editor: AppState::Editor,
                 when_game: AppState::Game,
             });
         } else {
-            app.add_plugins(MenuPlugin);
             app.add_plugins(LoadingPlugin);
-            // if let Some(start_at_level) = &self.start_at_level {
-            // let start_at_level = if start_at_level.ends_with(".yol") {
-            // start_at_level.clone()
-            // } else {
-            // format!("{}.yol", start_at_level)
-            // };
-            // app.add_systems(
-            // Startup,
-            // move |mut level_progress: ResMut<LevelProgress>,
-            // mut app_state: ResMut<NextState<AppState>>| {
-            // level_progress.current_level = Some(start_at_level.clone());
-            // app_state.set(AppState::LoadLevel);
-            // },
-            // );
-            // }
-            // app.add_systems(
-            // Startup,
-            // |asset_server: Res<AssetServer>, mut commands: Commands| {
-            // commands.spawn(YoleckLoadLevel(asset_server.load("levels/Level.yol")));
-            // },
-            // );
+            if let Some(ticks) = self.simulate_ticks {
+                app.add_plugins(SimulationPlugin { ticks });
+            } else {
+                app.add_plugins(MenuPlugin);
+                app.add_plugins(HighScoresPlugin {
+                    // Otherwise watching a replay would add the same run to the leaderboard again
+                    record_scores: !matches!(self.replay, Some(ReplayMode::Play(_))),
+                });
+            }
+            let start_at_level = match &self.replay {
+                Some(ReplayMode::Play(replay)) => Some(&replay.level),
+                _ => self.start_at_level.as_ref(),
+            };
+            if let Some(start_at_level) = start_at_level {
+                let start_at_level = if start_at_level.ends_with(".yol") {
+                    start_at_level.clone()
+                } else {
+                    format!("{}.yol", start_at_level)
+                };
+                app.insert_resource(CurrentLevel(start_at_level));
+            }
+            if start_at_level.is_some() || self.replay.is_some() || self.simulate_ticks.is_some() {
+                app.add_systems(Startup, |mut app_state: ResMut<NextState<AppState>>| {
+                    app_state.set(AppState::LoadLevel);
+                });
+            }
+            if let Some(replay) = &self.replay {
+                app.add_plugins(ReplayPlugin {
+                    mode: replay.clone(),
+                });
+            }
             app.insert_state(AppState::MainMenu);
         }
+        if self.simulate_ticks.is_none() {
+            // Simulations have no player, and should run for as many ticks as they were asked to
+            app.add_plugins((FailurePlugin, PlayerControlsPlugin));
+        }
         app.add_plugins((
-            ArenaPlugin,
             BedPlugin,
             DeskPlugin,
             DweebBehaviorPlugin,
             DweebEffectsPlugin,
+            DweebNeedsPlugin,
             DweebPlugin,
-            PlayerControlsPlugin,
+            FloorPlugin,
+            FocusPlugin,
+            LevelRulesPlugin,
+            NavigationPlugin,
+            ParticlesPlugin,
+            PlayerAttackPlugin,
             PlayerPlugin,
-            ScorePlugin,
+            ReservationPlugin,
+            ScorePlugin {
+                has_time_limit: self.simulate_ticks.is_none(),
+            },
+            SettingsPlugin,
+            WallPlugin,
         ));
 
-        app.add_systems(Update, enable_disable_physics);
+        app.add_systems(Update, sync_gameplay_clock);
     }
 }
 
@@ -96,6 +168,10 @@ pub enum During {
     Gameplay,
 }
 
+/// Systems that draw egui UI, which headless simulations don't have.
+#[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct DisplayUi;
+
 #[derive(States, Default, Clone, Hash, Debug, PartialEq, Eq)]
 pub enum AppState {
     #[default]
@@ -116,7 +192,7 @@ impl AppState {
             AppState::LoadLevel => false,
             AppState::Editor => false,
             AppState::Game => false,
-            AppState::LevelCompleted => false,
+            AppState::LevelCompleted => true,
             AppState::GameOver => true,
         }
     }
@@ -128,14 +204,16 @@ pub enum ActionForKbgp {
     RestartLevel,
 }
 
-fn enable_disable_physics(
+fn sync_gameplay_clock(
     state: Res<State<AppState>>,
+    mut gameplay_clock: ResMut<GameplayClock>,
     mut avian_time: ResMut<Time<avian3d::schedule::Physics>>,
 ) {
     use avian3d::schedule::PhysicsTime;
-    if matches!(state.get(), AppState::Game) {
-        avian_time.unpause();
-    } else {
+    gameplay_clock.set_paused(!matches!(state.get(), AppState::Game));
+    if gameplay_clock.is_paused() {
         avian_time.pause();
+    } else {
+        avian_time.unpause();
     }
 }
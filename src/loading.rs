@@ -7,12 +7,55 @@ pub struct LoadingPlugin;
 
 impl Plugin for LoadingPlugin {
     fn build(&self, app: &mut App) {
+        app.init_resource::<CurrentLevel>();
+        app.add_systems(Startup, load_level_index);
         app.add_systems(OnEnter(AppState::LoadLevel), load_the_level);
     }
 }
 
+/// The filename (relative to `assets/levels`) of the level to load on [`AppState::LoadLevel`].
+#[derive(Resource)]
+pub struct CurrentLevel(pub String);
+
+impl Default for CurrentLevel {
+    fn default() -> Self {
+        Self("Level.yol".to_owned())
+    }
+}
+
+/// The campaign's levels, in the order they should be played.
+#[derive(Resource)]
+pub struct LevelIndex(pub Handle<YoleckLevelIndex>);
+
+impl LevelIndex {
+    pub fn first_level(&self, level_index_assets: &Assets<YoleckLevelIndex>) -> Option<String> {
+        let level_index = level_index_assets.get(&self.0)?;
+        let first_entry = level_index.iter().next()?;
+        Some(first_entry.filename.clone())
+    }
+
+    pub fn level_after(
+        &self,
+        level_index_assets: &Assets<YoleckLevelIndex>,
+        level: &str,
+    ) -> Option<String> {
+        let level_index = level_index_assets.get(&self.0)?;
+        let mut entries = level_index
+            .iter()
+            .skip_while(|entry| entry.filename != level);
+        entries.next()?;
+        let next_entry = entries.next()?;
+        Some(next_entry.filename.clone())
+    }
+}
+
+fn load_level_index(asset_server: Res<AssetServer>, mut commands: Commands) {
+    commands.insert_resource(LevelIndex(asset_server.load("levels/index.yoli")));
+}
+
 fn load_the_level(
     asset_server: Res<AssetServer>,
+    current_level: Res<CurrentLevel>,
     existing_levels_query: Query<Entity, With<YoleckKeepLevel>>,
     mut commands: Commands,
     mut app_state: ResMut<NextState<AppState>>,
@@ -20,6 +63,8 @@ fn load_the_level(
     for existing_level in existing_levels_query.iter() {
         commands.entity(existing_level).despawn_recursive();
     }
-    commands.spawn(YoleckLoadLevel(asset_server.load("levels/Level.yol")));
+    commands.spawn(YoleckLoadLevel(
+        asset_server.load(format!("levels/{}", current_level.0)),
+    ));
     app_state.set(AppState::Game);
 }
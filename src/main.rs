@@ -6,17 +6,25 @@
 
 use avian3d::PhysicsPlugins;
 use bevy_egui::EguiPlugin;
-use bevy_egui_kbgp::{KbgpNavBindings, KbgpNavCommand, KbgpPlugin, KbgpSettings};
+use bevy_egui_kbgp::{KbgpPlugin, KbgpSettings};
 use bevy_tnua::controller::TnuaControllerPlugin;
 use bevy_tnua_avian3d::TnuaAvian3dPlugin;
 use bevy_turborand::prelude::RngPlugin;
 use bevy_yoleck::vpeol_3d::{Vpeol3dPluginForEditor, Vpeol3dPluginForGame};
 use bevy_yoleck::{YoleckPluginForEditor, YoleckPluginForGame};
 use clap::Parser;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
+use bevy::app::ScheduleRunnerPlugin;
 use bevy::asset::AssetMetaCheck;
 use bevy::prelude::*;
-use swift_dreams_are_made_for_dweebs::{ActionForKbgp, SwiftDreamsAreMadeForDweebsPlugin};
+use bevy::render::settings::{RenderCreation, WgpuSettings};
+use bevy::render::RenderPlugin;
+use bevy::time::TimeUpdateStrategy;
+use bevy::window::ExitCondition;
+use bevy::winit::WinitPlugin;
+use swift_dreams_are_made_for_dweebs::{Replay, ReplayMode, SwiftDreamsAreMadeForDweebsPlugin};
 
 #[derive(Parser, Debug)]
 struct Args {
@@ -24,32 +32,99 @@ struct Args {
     editor: bool,
     #[clap(long)]
     level: Option<String>,
+    /// Record the first level played into a replay file
+    #[clap(long, conflicts_with = "replay")]
+    record: Option<PathBuf>,
+    /// Play back a replay file recorded with --record
+    #[clap(long)]
+    replay: Option<PathBuf>,
+    /// Seed for the RNG (ignored when playing back a replay)
+    #[clap(long)]
+    seed: Option<u64>,
+    /// Simulate the level without rendering or a player and print statistics about the dweebs
+    #[clap(long, conflicts_with_all = ["editor", "record", "replay"])]
+    headless: bool,
+    /// Number of FixedUpdate ticks to simulate with --headless
+    #[clap(long, default_value_t = 64 * 60)]
+    ticks: u64,
 }
 
 fn main() {
     let args = Args::parse();
 
+    let replay = if let Some(path) = args.replay {
+        match Replay::load(&path) {
+            Ok(replay) => Some(ReplayMode::Play(replay)),
+            Err(err) => {
+                eprintln!("Unable to load replay from {}: {err}", path.display());
+                std::process::exit(1);
+            }
+        }
+    } else {
+        args.record.map(|path| ReplayMode::Record {
+            path,
+            seed: args.seed.unwrap_or_else(|| {
+                SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|duration| duration.as_nanos() as u64)
+                    .unwrap_or_default()
+            }),
+        })
+    };
+
     let mut app = App::new();
-    app.add_plugins(DefaultPlugins.set(AssetPlugin {
+    let default_plugins = DefaultPlugins.set(AssetPlugin {
         // Wasm builds will check for meta files (that don't exist) if this isn't set.
         // This causes errors and even panics in web builds on itch.
         // See https://github.com/bevyengine/bevy_github_ci_template/issues/48.
         meta_check: AssetMetaCheck::Never,
         ..default()
-    }));
-
-    app.add_plugins(EguiPlugin);
+    });
+    if args.headless {
+        app.add_plugins(
+            default_plugins
+                .set(WindowPlugin {
+                    primary_window: None,
+                    exit_condition: ExitCondition::DontExit,
+                    close_when_requested: false,
+                })
+                .set(RenderPlugin {
+                    render_creation: RenderCreation::Automatic(WgpuSettings {
+                        backends: None,
+                        ..default()
+                    }),
+                    ..default()
+                })
+                .disable::<WinitPlugin>(),
+        );
+        app.add_plugins(ScheduleRunnerPlugin::run_loop(Duration::ZERO));
+        // Advance exactly one fixed timestep per frame, so that the simulation runs as fast as
+        // possible without changing its results
+        app.insert_resource(TimeUpdateStrategy::ManualDuration(
+            Time::<Fixed>::default().timestep(),
+        ));
+    } else {
+        app.add_plugins(default_plugins);
+        app.add_plugins(EguiPlugin);
+    }
     app.add_plugins((
-        PhysicsPlugins::default(),
-        TnuaControllerPlugin::default(),
-        TnuaAvian3dPlugin::default(),
+        // Fixed timesteps, so that replays and simulations don't depend on the frame rate
+        PhysicsPlugins::new(FixedPostUpdate),
+        TnuaControllerPlugin::new(FixedUpdate),
+        TnuaAvian3dPlugin::new(FixedUpdate),
     ));
-    app.add_plugins(RngPlugin::default());
+    if let Some(seed) = replay.as_ref().map(ReplayMode::seed).or(args.seed) {
+        app.add_plugins(RngPlugin::new().with_rng_seed(seed));
+    } else {
+        app.add_plugins(RngPlugin::default());
+    }
 
     if args.editor {
         app.add_plugins((YoleckPluginForEditor, Vpeol3dPluginForEditor::topdown()));
     } else {
         app.add_plugins((YoleckPluginForGame, Vpeol3dPluginForGame));
+    }
+    if !args.editor && !args.headless {
         app.add_plugins(KbgpPlugin);
         app.insert_resource(KbgpSettings {
             disable_default_navigation: true,
@@ -61,31 +136,16 @@ fn main() {
             allow_mouse_wheel: false,
             allow_mouse_wheel_sideways: false,
             allow_gamepads: true,
-            bindings: {
-                KbgpNavBindings::default()
-                    .with_wasd_navigation()
-                    .with_key(KeyCode::Escape, KbgpNavCommand::user(ActionForKbgp::Menu))
-                    .with_key(
-                        KeyCode::Backspace,
-                        KbgpNavCommand::user(ActionForKbgp::RestartLevel),
-                    )
-                    .with_key(KeyCode::Space, KbgpNavCommand::Click)
-                    .with_key(KeyCode::KeyJ, KbgpNavCommand::Click)
-                    .with_gamepad_button(
-                        GamepadButtonType::Start,
-                        KbgpNavCommand::user(ActionForKbgp::Menu),
-                    )
-                    .with_gamepad_button(
-                        GamepadButtonType::Select,
-                        KbgpNavCommand::user(ActionForKbgp::RestartLevel),
-                    )
-            },
+            // Set from the player's settings by the game plugin
+            bindings: Default::default(),
         });
     }
 
     app.add_plugins(SwiftDreamsAreMadeForDweebsPlugin {
         is_editor: args.editor,
         start_at_level: args.level,
+        replay,
+        simulate_ticks: args.headless.then_some(args.ticks),
     });
     app.run();
 }
@@ -1,25 +1,39 @@
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 use bevy_egui_kbgp::prelude::*;
+use bevy_yoleck::prelude::*;
 
-use crate::{score::GameData, ActionForKbgp, AppState, During};
+use crate::{
+    high_scores::{HighScores, LastRunRank},
+    loading::{CurrentLevel, LevelIndex},
+    score::{GameData, GameOverReason},
+    settings::{BindableAction, Binding, InputBindings, Settings},
+    ActionForKbgp, AppState, During,
+};
 
 pub struct MenuPlugin;
 
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<FrameUi>();
+        app.init_resource::<SettingsScreen>();
         app.add_systems(Update, handle_user_kbgp_actions.in_set(During::Gameplay));
         app.add_systems(
             Update,
             (
                 prepare_menu,
                 menu_header,
-                main_menu.run_if(in_state(AppState::MainMenu)),
-                pause_menu.run_if(in_state(AppState::PauseMenu)),
+                settings_menu.run_if(settings_screen_is_open),
+                main_menu
+                    .run_if(in_state(AppState::MainMenu))
+                    .run_if(not(settings_screen_is_open)),
+                pause_menu
+                    .run_if(in_state(AppState::PauseMenu))
+                    .run_if(not(settings_screen_is_open)),
+                level_completed_menu.run_if(in_state(AppState::LevelCompleted)),
                 game_over_menu.run_if(in_state(AppState::GameOver)),
                 #[cfg(not(target_arch = "wasm32"))]
-                exit_button,
+                exit_button.run_if(not(settings_screen_is_open)),
                 draw_menu,
             )
                 .chain()
@@ -52,6 +66,8 @@ pub enum FocusLabel {
     Exit,
     NextLevel,
     BackToMainMenu,
+    Settings,
+    BackFromSettings,
 }
 
 #[derive(Resource, Default)]
@@ -153,7 +169,14 @@ fn menu_header(mut frame_ui: ResMut<FrameUi>) {
     ui.add_space(10.0);
 }
 
-fn main_menu(mut frame_ui: ResMut<FrameUi>, mut next_state: ResMut<NextState<AppState>>) {
+fn main_menu(
+    mut frame_ui: ResMut<FrameUi>,
+    level_index: Res<LevelIndex>,
+    level_index_assets: Res<Assets<YoleckLevelIndex>>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut settings_screen: ResMut<SettingsScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
     let Some(ui) = frame_ui.0.as_mut() else {
         return;
     };
@@ -164,13 +187,21 @@ fn main_menu(mut frame_ui: ResMut<FrameUi>, mut next_state: ResMut<NextState<App
         .kbgp_initial_focus()
         .kbgp_click_released()
     {
+        if let Some(first_level) = level_index.first_level(&level_index_assets) {
+            current_level.0 = first_level;
+        }
         next_state.set(AppState::LoadLevel);
         ui.kbgp_clear_input();
         ui.kbgp_set_focus_label(FocusLabel::NextLevel);
     }
+    settings_button(ui, &mut settings_screen);
 }
 
-fn pause_menu(mut frame_ui: ResMut<FrameUi>, mut next_state: ResMut<NextState<AppState>>) {
+fn pause_menu(
+    mut frame_ui: ResMut<FrameUi>,
+    mut settings_screen: ResMut<SettingsScreen>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
     let Some(ui) = frame_ui.0.as_mut() else {
         return;
     };
@@ -186,6 +217,7 @@ fn pause_menu(mut frame_ui: ResMut<FrameUi>, mut next_state: ResMut<NextState<Ap
     if ui.button("Retry").kbgp_navigation().kbgp_click_released() {
         next_state.set(AppState::LoadLevel);
     }
+    settings_button(ui, &mut settings_screen);
     if ui.button("Main Menu").kbgp_navigation().clicked() {
         next_state.set(AppState::MainMenu);
         ui.kbgp_clear_input();
@@ -193,15 +225,234 @@ fn pause_menu(mut frame_ui: ResMut<FrameUi>, mut next_state: ResMut<NextState<Ap
     }
 }
 
+/// Reserved for canceling the capture, so they cannot be bound.
+const CANCEL_CAPTURE_KEY: KeyCode = KeyCode::Escape;
+const CANCEL_CAPTURE_BUTTON: GamepadButtonType = GamepadButtonType::Start;
+
+#[derive(Resource, Default)]
+struct SettingsScreen {
+    is_open: bool,
+    capturing: Option<BindableAction>,
+}
+
+fn settings_screen_is_open(settings_screen: Res<SettingsScreen>) -> bool {
+    settings_screen.is_open
+}
+
+fn settings_button(ui: &mut egui::Ui, settings_screen: &mut SettingsScreen) {
+    if ui
+        .button("Settings")
+        .kbgp_navigation()
+        .kbgp_focus_label(FocusLabel::Settings)
+        .kbgp_click_released()
+    {
+        settings_screen.is_open = true;
+        ui.kbgp_clear_input();
+        ui.kbgp_set_focus_label(FocusLabel::BackFromSettings);
+    }
+}
+
+fn settings_menu(
+    mut frame_ui: ResMut<FrameUi>,
+    mut settings_screen: ResMut<SettingsScreen>,
+    mut settings: ResMut<Settings>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    gamepad_buttons: Res<ButtonInput<GamepadButton>>,
+) {
+    let Some(ui) = frame_ui.0.as_mut() else {
+        return;
+    };
+
+    if let Some(action) = settings_screen.capturing {
+        ui.label(
+            egui::RichText::new(format!("Press a key or a button for {action}"))
+                .size(30.0)
+                .strong()
+                .color(egui::Color32::LIGHT_BLUE),
+        );
+        ui.label(
+            egui::RichText::new("(Escape or Start to cancel)")
+                .size(20.0)
+                .color(egui::Color32::WHITE),
+        );
+        let cancelled = keyboard.just_pressed(CANCEL_CAPTURE_KEY)
+            || gamepad_buttons
+                .get_just_pressed()
+                .any(|button| button.button_type == CANCEL_CAPTURE_BUTTON);
+        if cancelled {
+            settings_screen.capturing = None;
+            // So that the key or button will not also close the settings screen
+            ui.kbgp_clear_input();
+            return;
+        }
+        let pressed = keyboard
+            .get_just_pressed()
+            .next()
+            .map(|key| Binding::Key(*key))
+            .or_else(|| {
+                gamepad_buttons
+                    .get_just_pressed()
+                    .next()
+                    .map(|button| Binding::GamepadButton(button.button_type))
+            });
+        if let Some(binding) = pressed {
+            let bindings = settings.bindings.get_mut(action);
+            if !bindings.contains(&binding) {
+                bindings.push(binding);
+            }
+            settings_screen.capturing = None;
+            // So that the key or button will not also activate a widget
+            ui.kbgp_clear_input();
+        }
+        return;
+    }
+
+    for action in BindableAction::ALL {
+        ui.horizontal(|ui| {
+            let bindings = settings.bindings.get(action);
+            let bindings_text = if bindings.is_empty() {
+                "(unbound)".to_owned()
+            } else {
+                bindings
+                    .iter()
+                    .map(|binding| binding.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            };
+            ui.label(
+                egui::RichText::new(format!("{action}: {bindings_text}"))
+                    .size(20.0)
+                    .strong()
+                    .color(egui::Color32::WHITE),
+            );
+            if ui.button("Add").kbgp_navigation().kbgp_click_released() {
+                settings_screen.capturing = Some(action);
+            }
+            if ui.button("Clear").kbgp_navigation().kbgp_click_released() {
+                settings.bindings.get_mut(action).clear();
+            }
+        });
+    }
+    ui.add_space(20.0);
+    ui.horizontal(|ui| {
+        ui.label(
+            egui::RichText::new("Aim Assist:")
+                .size(20.0)
+                .strong()
+                .color(egui::Color32::WHITE),
+        );
+        // Not passing the setting directly, because the slider would mark it as changed (and
+        // therefore save it) every frame
+        let mut aim_assist = settings.aim_assist;
+        if ui
+            .add(egui::Slider::new(&mut aim_assist, 0.0..=1.0))
+            .kbgp_navigation()
+            .changed()
+        {
+            settings.aim_assist = aim_assist;
+        }
+    });
+    ui.add_space(20.0);
+    if ui
+        .button("Reset to Defaults")
+        .kbgp_navigation()
+        .kbgp_click_released()
+    {
+        settings.bindings = InputBindings::default();
+    }
+    if ui
+        .button("Back")
+        .kbgp_navigation()
+        .kbgp_focus_label(FocusLabel::BackFromSettings)
+        .kbgp_initial_focus()
+        .kbgp_click_released()
+        || ui.kbgp_user_action() == Some(ActionForKbgp::Menu)
+    {
+        settings_screen.is_open = false;
+        ui.kbgp_clear_input();
+        ui.kbgp_set_focus_label(FocusLabel::Settings);
+    }
+}
+
+fn level_completed_menu(
+    mut frame_ui: ResMut<FrameUi>,
+    game_data: Res<GameData>,
+    high_scores: Res<HighScores>,
+    last_run_rank: Res<LastRunRank>,
+    level_index: Res<LevelIndex>,
+    level_index_assets: Res<Assets<YoleckLevelIndex>>,
+    mut current_level: ResMut<CurrentLevel>,
+    mut next_state: ResMut<NextState<AppState>>,
+) {
+    let Some(ui) = frame_ui.0.as_mut() else {
+        return;
+    };
+    let next_level = level_index.level_after(&level_index_assets, &current_level.0);
+    ui.label(
+        egui::RichText::new(if next_level.is_some() {
+            "Level Completed"
+        } else {
+            "All Levels Completed"
+        })
+        .size(50.0)
+        .strong()
+        .color(egui::Color32::LIGHT_BLUE),
+    );
+    ui.label(
+        egui::RichText::new(format!(
+            "The dweebs have managed\nto scribe {} ideas\nfor a score of {}",
+            game_data.ideas_scribed(),
+            game_data.score()
+        ))
+        .size(30.0)
+        .strong()
+        .color(egui::Color32::LIGHT_GREEN),
+    );
+    leaderboard(ui, &high_scores, &current_level, &last_run_rank);
+    ui.add_space(20.0);
+    if ui.kbgp_user_action() == Some(ActionForKbgp::Menu) {
+        ui.kbgp_set_focus_label(FocusLabel::BackToMainMenu);
+    }
+    if let Some(next_level) = next_level {
+        if ui
+            .button("Next Level")
+            .kbgp_navigation()
+            .kbgp_focus_label(FocusLabel::NextLevel)
+            .kbgp_initial_focus()
+            .clicked()
+        {
+            current_level.0 = next_level;
+            next_state.set(AppState::LoadLevel);
+        }
+    }
+    if ui.button("Retry").kbgp_navigation().clicked() {
+        next_state.set(AppState::LoadLevel);
+    }
+    if ui
+        .button("Main Menu")
+        .kbgp_navigation()
+        .kbgp_focus_label(FocusLabel::BackToMainMenu)
+        .clicked()
+    {
+        next_state.set(AppState::MainMenu);
+        ui.kbgp_clear_input();
+        ui.kbgp_set_focus_label(FocusLabel::Start);
+    }
+}
+
 fn game_over_menu(
     mut frame_ui: ResMut<FrameUi>,
     game_data: Res<GameData>,
+    high_scores: Res<HighScores>,
+    current_level: Res<CurrentLevel>,
+    last_run_rank: Res<LastRunRank>,
     mut next_state: ResMut<NextState<AppState>>,
 ) {
     let Some(ui) = frame_ui.0.as_mut() else {
         return;
     };
-    if game_data.is_finished() {
+    let reason = game_data.game_over_reason();
+    if reason == Some(GameOverReason::TimeOut) {
         ui.label(
             egui::RichText::new("Time Out")
                 .size(50.0)
@@ -210,8 +461,10 @@ fn game_over_menu(
         );
         ui.label(
             egui::RichText::new(format!(
-                "The dweebs have managed\nto scribe {} ideas",
-                game_data.score()
+                "The dweebs have managed\nto scribe {} ideas\nfor {} of {} points",
+                game_data.ideas_scribed(),
+                game_data.score(),
+                game_data.target_score()
             ))
             .size(30.0)
             .strong()
@@ -224,7 +477,24 @@ fn game_over_menu(
                 .strong()
                 .color(egui::Color32::RED),
         );
+        let explanation = match reason {
+            Some(GameOverReason::TimeOut) | None => None,
+            Some(GameOverReason::PlayerFell) => Some("You fell out of the dormitory"),
+            Some(GameOverReason::DweebKnockedOff) => {
+                Some("A dweeb was knocked out of the dormitory")
+            }
+            Some(GameOverReason::TooManyStartled) => Some("Too many dweebs are awake at once"),
+        };
+        if let Some(explanation) = explanation {
+            ui.label(
+                egui::RichText::new(explanation)
+                    .size(30.0)
+                    .strong()
+                    .color(egui::Color32::LIGHT_RED),
+            );
+        }
     }
+    leaderboard(ui, &high_scores, &current_level, &last_run_rank);
     ui.add_space(20.0);
     if ui.kbgp_user_action() == Some(ActionForKbgp::Menu) {
         ui.kbgp_set_focus_label(FocusLabel::BackToMainMenu);
@@ -244,6 +514,47 @@ fn game_over_menu(
     }
 }
 
+fn leaderboard(
+    ui: &mut egui::Ui,
+    high_scores: &HighScores,
+    current_level: &CurrentLevel,
+    last_run_rank: &LastRunRank,
+) {
+    if last_run_rank.is_new_best() {
+        ui.label(
+            egui::RichText::new("New best!")
+                .size(30.0)
+                .strong()
+                .color(egui::Color32::GOLD),
+        );
+    }
+    for (rank, entry) in high_scores.leaderboard(&current_level.0).iter().enumerate() {
+        let mut text = format!(
+            "{}. {} points ({} ideas)",
+            rank + 1,
+            entry.score,
+            entry.ideas_scribed
+        );
+        if let Some(completion_time) = entry.completion_time {
+            text.push_str(&format!(" in {:.1}s", completion_time));
+        }
+        ui.label(egui::RichText::new(text).size(20.0).strong().color(
+            if last_run_rank.0 == Some(rank) {
+                egui::Color32::GOLD
+            } else {
+                egui::Color32::WHITE
+            },
+        ));
+    }
+    if let Some(best_time) = high_scores.best_time(&current_level.0) {
+        ui.label(
+            egui::RichText::new(format!("Best time: {:.1}s", best_time))
+                .size(20.0)
+                .color(egui::Color32::LIGHT_BLUE),
+        );
+    }
+}
+
 #[allow(dead_code)]
 fn exit_button(mut frame_ui: ResMut<FrameUi>, mut exit: EventWriter<bevy::app::AppExit>) {
     let Some(ui) = frame_ui.0.as_mut() else {
@@ -0,0 +1,335 @@
+//! Grid pathfinding for the dweebs, so that they can walk around walls and furniture.
+//!
+//! The grid covers the [`Ground`] colliders, and every other static collider blocks the cells under
+//! it. It is rebuilt whenever a static collider is added, moved or removed.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+
+use avian3d::prelude::*;
+use bevy::{prelude::*, transform::TransformSystem, utils::HashMap};
+
+pub struct NavigationPlugin;
+
+impl Plugin for NavigationPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<NavGrid>();
+        app.add_systems(
+            PostUpdate,
+            rebuild_nav_grid.after(TransformSystem::TransformPropagate),
+        );
+    }
+}
+
+const CELL_SIZE: f32 = 0.5;
+/// How far from obstacles the center of a dweeb must stay.
+const AGENT_RADIUS: f32 = 0.5;
+/// Obstacles lower than this (above the ground under them) can be stepped over.
+const STEP_HEIGHT: f32 = 0.3;
+/// How far from a blocked destination to look for a walkable cell, in cells.
+const MAX_DESTINATION_SEARCH_RADIUS: i32 = 10;
+
+/// Marks a collider that the dweebs walk on, rather than around.
+#[derive(Component)]
+pub struct Ground;
+
+#[derive(Resource, Default)]
+pub struct NavGrid {
+    /// Incremented on each rebuild, so that paths know when to recalculate.
+    generation: u64,
+    origin: Vec2,
+    size: IVec2,
+    blocked: Vec<bool>,
+}
+
+impl NavGrid {
+    fn cell_of(&self, position: Vec3) -> Option<IVec2> {
+        let cell = ((position.xz() - self.origin) / CELL_SIZE)
+            .floor()
+            .as_ivec2();
+        self.index(cell).map(|_| cell)
+    }
+
+    fn index(&self, cell: IVec2) -> Option<usize> {
+        if cell.cmplt(IVec2::ZERO).any() || cell.cmpge(self.size).any() {
+            return None;
+        }
+        Some((cell.y * self.size.x + cell.x) as usize)
+    }
+
+    fn cell_center(&self, cell: IVec2, y: f32) -> Vec3 {
+        let xz = self.origin + (cell.as_vec2() + 0.5) * CELL_SIZE;
+        Vec3::new(xz.x, y, xz.y)
+    }
+
+    fn is_walkable(&self, cell: IVec2) -> bool {
+        self.index(cell).is_some_and(|index| !self.blocked[index])
+    }
+
+    fn nearest_walkable(&self, cell: IVec2) -> Option<IVec2> {
+        for radius in 0..=MAX_DESTINATION_SEARCH_RADIUS {
+            let ring = (-radius..=radius)
+                .flat_map(|dx| (-radius..=radius).map(move |dy| IVec2::new(dx, dy)))
+                .filter(|offset| offset.x.abs() == radius || offset.y.abs() == radius);
+            if let Some(offset) = ring
+                .filter(|offset| self.is_walkable(cell + *offset))
+                .min_by_key(|offset| offset.length_squared())
+            {
+                return Some(cell + offset);
+            }
+        }
+        None
+    }
+
+    /// Blocked cells at the beginning of the line are ignored, so that a dweeb that got pushed
+    /// against an obstacle can still walk away from it.
+    fn has_line_of_sight(&self, from: Vec3, to: Vec3) -> bool {
+        let steps = (from.xz().distance(to.xz()) / (0.5 * CELL_SIZE)).ceil() as usize;
+        let mut left_blocked_area = false;
+        for step in 0..=steps {
+            let position = from.lerp(to, step as f32 / steps.max(1) as f32);
+            let Some(cell) = self.cell_of(position) else {
+                return false;
+            };
+            if self.is_walkable(cell) {
+                left_blocked_area = true;
+            } else if left_blocked_area {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Returns the waypoints to go through, ending with `to` itself. If `to` is inside an obstacle
+    /// (e.g. a bed), the path leads to the closest walkable point and goes straight from there.
+    pub fn find_path(&self, from: Vec3, to: Vec3) -> Option<Vec<Vec3>> {
+        const STRAIGHT_COST: u32 = 10;
+        const DIAGONAL_COST: u32 = 14;
+        let heuristic = |cell: IVec2, goal: IVec2| {
+            let delta = (goal - cell).abs();
+            STRAIGHT_COST * delta.max_element() as u32
+                + (DIAGONAL_COST - STRAIGHT_COST) * delta.min_element() as u32
+        };
+
+        let start = self.cell_of(from)?;
+        let goal = self.nearest_walkable(self.cell_of(to)?)?;
+
+        let mut open = BinaryHeap::new();
+        let mut came_from = HashMap::<IVec2, IVec2>::new();
+        let mut costs = HashMap::<IVec2, u32>::new();
+        open.push(Reverse((heuristic(start, goal), start.to_array())));
+        costs.insert(start, 0);
+        while let Some(Reverse((_, cell))) = open.pop() {
+            let cell = IVec2::from_array(cell);
+            if cell == goal {
+                break;
+            }
+            let cost = costs[&cell];
+            // Blocked cells can only be walked through in order to get out of a blocked area
+            let is_escaping = !self.is_walkable(cell);
+            let is_passable =
+                |cell: IVec2| self.is_walkable(cell) || (is_escaping && self.index(cell).is_some());
+            for dx in -1..=1 {
+                for dy in -1..=1 {
+                    let neighbor = cell + IVec2::new(dx, dy);
+                    if neighbor == cell || !is_passable(neighbor) {
+                        continue;
+                    }
+                    let is_diagonal = dx != 0 && dy != 0;
+                    if is_diagonal
+                        && !(is_passable(cell + IVec2::new(dx, 0))
+                            && is_passable(cell + IVec2::new(0, dy)))
+                    {
+                        // Don't cut corners
+                        continue;
+                    }
+                    let neighbor_cost = cost
+                        + if is_diagonal {
+                            DIAGONAL_COST
+                        } else {
+                            STRAIGHT_COST
+                        };
+                    if costs
+                        .get(&neighbor)
+                        .is_some_and(|existing_cost| *existing_cost <= neighbor_cost)
+                    {
+                        continue;
+                    }
+                    costs.insert(neighbor, neighbor_cost);
+                    came_from.insert(neighbor, cell);
+                    open.push(Reverse((
+                        neighbor_cost + heuristic(neighbor, goal),
+                        neighbor.to_array(),
+                    )));
+                }
+            }
+        }
+        if !costs.contains_key(&goal) {
+            return None;
+        }
+
+        let mut cells = vec![goal];
+        while let Some(previous) = came_from.get(cells.last().unwrap()) {
+            cells.push(*previous);
+        }
+        cells.pop(); // The start cell
+        cells.reverse();
+
+        // Skip the waypoints that can be bypassed in a straight line
+        let points = cells
+            .into_iter()
+            .map(|cell| self.cell_center(cell, to.y))
+            .collect::<Vec<_>>();
+        let mut waypoints = Vec::new();
+        let mut anchor = from;
+        let mut index = 0;
+        while index < points.len() {
+            let mut farthest = index;
+            while farthest + 1 < points.len()
+                && self.has_line_of_sight(anchor, points[farthest + 1])
+            {
+                farthest += 1;
+            }
+            anchor = points[farthest];
+            waypoints.push(anchor);
+            index = farthest + 1;
+        }
+        waypoints.push(to);
+        Some(waypoints)
+    }
+}
+
+/// A dweeb's current path.
+#[derive(Component, Default)]
+pub struct NavPath {
+    goal: Option<Vec3>,
+    generation: u64,
+    waypoints: VecDeque<Vec3>,
+}
+
+impl NavPath {
+    /// Returns the point to walk toward in order to get to `goal`.
+    pub fn steer(&mut self, grid: &NavGrid, position: Vec3, goal: Vec3) -> Vec3 {
+        let is_stale = self
+            .goal
+            .map_or(true, |current_goal| {
+                CELL_SIZE < current_goal.xz().distance(goal.xz())
+            })
+            || self.generation != grid.generation
+            // The last waypoint is the goal itself, which may be inside an obstacle
+            || (1 < self.waypoints.len()
+                && !grid.has_line_of_sight(position, self.waypoints[0]));
+        if is_stale {
+            self.goal = Some(goal);
+            self.generation = grid.generation;
+            self.waypoints = grid.find_path(position, goal).unwrap_or_default().into();
+        }
+        while 1 < self.waypoints.len() && self.waypoints[0].xz().distance(position.xz()) < CELL_SIZE
+        {
+            self.waypoints.pop_front();
+        }
+        self.waypoints.front().copied().unwrap_or(goal)
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn rebuild_nav_grid(
+    mut nav_grid: ResMut<NavGrid>,
+    changed_query: Query<
+        &RigidBody,
+        (
+            With<Collider>,
+            Or<(Changed<GlobalTransform>, Changed<Collider>)>,
+        ),
+    >,
+    mut removed_colliders: RemovedComponents<Collider>,
+    ground_query: Query<(&Collider, &GlobalTransform), With<Ground>>,
+    obstacles_query: Query<(&RigidBody, &Collider, &GlobalTransform), Without<Ground>>,
+) {
+    let has_removed_colliders = removed_colliders.read().count() != 0;
+    if !has_removed_colliders
+        && !changed_query
+            .iter()
+            .any(|rigid_body| rigid_body.is_static())
+    {
+        return;
+    }
+
+    let generation = nav_grid.generation + 1;
+    let ground_aabbs = ground_query
+        .iter()
+        .map(|(collider, transform)| {
+            collider.aabb(
+                transform.translation(),
+                transform.compute_transform().rotation,
+            )
+        })
+        .collect::<Vec<_>>();
+    if ground_aabbs.is_empty() {
+        *nav_grid = NavGrid {
+            generation,
+            ..Default::default()
+        };
+        return;
+    }
+    let min = ground_aabbs
+        .iter()
+        .fold(Vec3::INFINITY, |min, aabb| min.min(aabb.min));
+    let max = ground_aabbs
+        .iter()
+        .fold(Vec3::NEG_INFINITY, |max, aabb| max.max(aabb.max));
+    let size = ((max.xz() - min.xz()) / CELL_SIZE).ceil().as_ivec2();
+    *nav_grid = NavGrid {
+        generation,
+        origin: min.xz(),
+        size,
+        blocked: vec![false; (size.x * size.y) as usize],
+    };
+    // Floors can be at different heights, so each cell is measured against the ground under it.
+    // Cells with no ground under them don't have a height to step from.
+    let ground_heights = (0..size.y)
+        .flat_map(|y| (0..size.x).map(move |x| IVec2::new(x, y)))
+        .map(|cell| {
+            let center = nav_grid.cell_center(cell, 0.0).xz();
+            ground_aabbs
+                .iter()
+                .filter(|aabb| {
+                    aabb.min.xz().cmple(center).all() && center.cmple(aabb.max.xz()).all()
+                })
+                .map(|aabb| aabb.max.y)
+                .fold(f32::NEG_INFINITY, f32::max)
+        })
+        .collect::<Vec<_>>();
+
+    for (rigid_body, collider, transform) in obstacles_query.iter() {
+        if !rigid_body.is_static() {
+            continue;
+        }
+        let aabb = collider.aabb(
+            transform.translation(),
+            transform.compute_transform().rotation,
+        );
+        let from_cell = ((aabb.min.xz() - AGENT_RADIUS - nav_grid.origin) / CELL_SIZE)
+            .floor()
+            .as_ivec2()
+            .max(IVec2::ZERO);
+        let to_cell = ((aabb.max.xz() + AGENT_RADIUS - nav_grid.origin) / CELL_SIZE)
+            .floor()
+            .as_ivec2()
+            .min(nav_grid.size - 1);
+        for x in from_cell.x..=to_cell.x {
+            for y in from_cell.y..=to_cell.y {
+                let cell = IVec2::new(x, y);
+                let center = nav_grid.cell_center(cell, 0.0).xz();
+                if (aabb.min.xz() - AGENT_RADIUS).cmple(center).all()
+                    && center.cmple(aabb.max.xz() + AGENT_RADIUS).all()
+                {
+                    let index = nav_grid.index(cell).unwrap();
+                    if ground_heights[index] + STEP_HEIGHT <= aabb.max.y {
+                        nav_grid.blocked[index] = true;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,134 @@
+//! A small CPU particle system. Each particle is an entity moved by a regular system, so it works
+//! everywhere - including wasm, where there are no compute shaders.
+
+use bevy::prelude::*;
+
+use crate::{gameplay_time::GameplayTime, AppState};
+
+pub struct ParticlesPlugin;
+
+impl Plugin for ParticlesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, update_particles);
+        app.add_systems(OnEnter(AppState::LoadLevel), clear_particles);
+        app.add_systems(OnEnter(AppState::Editor), clear_particles);
+    }
+}
+
+/// How a particle looks. Cloned into each particle, except for the material which is created per
+/// particle so that it can fade on its own.
+#[derive(Clone)]
+pub struct ParticleVisual {
+    pub mesh: Handle<Mesh>,
+    /// Applied to the mesh before the particle's own transform, for meshes that are not centered
+    /// or upright.
+    pub mesh_transform: Transform,
+    pub color: Color,
+}
+
+#[derive(Component)]
+pub struct Particle {
+    pub lifetime: Timer,
+    pub velocity: Vec3,
+    pub acceleration: Vec3,
+    /// In radians per second, around the vertical axis that goes through `swirl_center`.
+    pub swirl_speed: f32,
+    pub swirl_center: Vec3,
+    /// At the beginning and at the end of the particle's life.
+    pub scale: [f32; 2],
+    /// At the beginning and at the end of the particle's life.
+    pub alpha: [f32; 2],
+}
+
+impl Particle {
+    pub fn with_lifetime(secs: f32) -> Self {
+        Self {
+            lifetime: Timer::from_seconds(secs, TimerMode::Once),
+            velocity: Vec3::ZERO,
+            acceleration: Vec3::ZERO,
+            swirl_speed: 0.0,
+            swirl_center: Vec3::ZERO,
+            scale: [1.0, 1.0],
+            alpha: [1.0, 1.0],
+        }
+    }
+}
+
+#[derive(Component)]
+struct ParticleMaterial(Handle<StandardMaterial>);
+
+pub fn spawn_particle(
+    commands: &mut Commands,
+    materials: &mut Assets<StandardMaterial>,
+    visual: &ParticleVisual,
+    position: Vec3,
+    particle: Particle,
+) {
+    let material = materials.add(StandardMaterial {
+        base_color: visual.color.with_alpha(particle.alpha[0]),
+        alpha_mode: AlphaMode::Blend,
+        ..Default::default()
+    });
+    commands
+        .spawn((
+            SpatialBundle::from_transform(
+                Transform::from_translation(position).with_scale(Vec3::splat(particle.scale[0])),
+            ),
+            ParticleMaterial(material.clone()),
+            particle,
+        ))
+        .with_children(|commands| {
+            commands.spawn(PbrBundle {
+                mesh: visual.mesh.clone(),
+                material,
+                transform: visual.mesh_transform,
+                ..Default::default()
+            });
+        });
+}
+
+fn update_particles(
+    time: GameplayTime,
+    mut query: Query<(Entity, &mut Particle, &mut Transform, &ParticleMaterial)>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut commands: Commands,
+) {
+    let delta_secs = time.delta_seconds();
+    if delta_secs == 0.0 {
+        return;
+    }
+    for (entity, mut particle, mut transform, ParticleMaterial(material)) in query.iter_mut() {
+        if particle.lifetime.tick(time.delta()).finished() {
+            commands.entity(entity).despawn_recursive();
+            continue;
+        }
+        let progress = particle.lifetime.fraction();
+
+        let acceleration = particle.acceleration;
+        particle.velocity += delta_secs * acceleration;
+        transform.translation += delta_secs * particle.velocity;
+        if particle.swirl_speed != 0.0 {
+            let rotation = Quat::from_rotation_y(delta_secs * particle.swirl_speed);
+            let center = particle.swirl_center.with_y(transform.translation.y);
+            transform.translation = center + rotation * (transform.translation - center);
+            transform.rotation = rotation * transform.rotation;
+        }
+
+        let [start_scale, end_scale] = particle.scale;
+        transform.scale = Vec3::splat(start_scale + (end_scale - start_scale) * progress);
+        let [start_alpha, end_alpha] = particle.alpha;
+        if start_alpha != end_alpha {
+            if let Some(material) = materials.get_mut(material) {
+                material
+                    .base_color
+                    .set_alpha(start_alpha + (end_alpha - start_alpha) * progress);
+            }
+        }
+    }
+}
+
+fn clear_particles(query: Query<Entity, With<Particle>>, mut commands: Commands) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -0,0 +1,107 @@
+//! Storing small JSON documents between runs - in the user's data directory on native builds and
+//! in the browser's local storage on wasm.
+
+use serde::{de::DeserializeOwned, Serialize};
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use native::*;
+#[cfg(target_arch = "wasm32")]
+pub use wasm::*;
+
+fn deserialize<T: DeserializeOwned>(name: &str, data: &str) -> Option<T> {
+    serde_json::from_str(data)
+        .inspect_err(|err| bevy::log::warn!("Unable to parse {name}: {err}"))
+        .ok()
+}
+
+fn serialize<T: Serialize>(name: &str, value: &T) -> Option<String> {
+    serde_json::to_string(value)
+        .inspect_err(|err| bevy::log::warn!("Unable to serialize {name}: {err}"))
+        .ok()
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod native {
+    use std::path::PathBuf;
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    use bevy::log::warn;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    fn path(name: &str) -> Option<PathBuf> {
+        Some(
+            dirs::data_dir()?
+                .join("swift-dreams-are-made-for-dweebs")
+                .join(format!("{name}.json")),
+        )
+    }
+
+    pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+        let data = std::fs::read_to_string(path(name)?).ok()?;
+        super::deserialize(name, &data)
+    }
+
+    pub fn save<T: Serialize>(name: &str, value: &T) {
+        let Some(path) = path(name) else {
+            warn!("Unable to find the data directory - {name} will not be saved");
+            return;
+        };
+        let Some(data) = super::serialize(name, value) else {
+            return;
+        };
+        let result = (|| -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, data)
+        })();
+        if let Err(err) = result {
+            warn!("Unable to save {name} to {}: {err}", path.display());
+        }
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn now_timestamp() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|duration| duration.as_secs())
+            .unwrap_or_default()
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod wasm {
+    use bevy::log::warn;
+    use serde::{de::DeserializeOwned, Serialize};
+
+    fn local_storage() -> Option<web_sys::Storage> {
+        web_sys::window()?.local_storage().ok()?
+    }
+
+    fn key(name: &str) -> String {
+        format!("swift-dreams-are-made-for-dweebs.{name}")
+    }
+
+    pub fn load<T: DeserializeOwned>(name: &str) -> Option<T> {
+        let data = local_storage()?.get_item(&key(name)).ok()??;
+        super::deserialize(name, &data)
+    }
+
+    pub fn save<T: Serialize>(name: &str, value: &T) {
+        let Some(local_storage) = local_storage() else {
+            warn!("Local storage is unavailable - {name} will not be saved");
+            return;
+        };
+        let Some(data) = super::serialize(name, value) else {
+            return;
+        };
+        if local_storage.set_item(&key(name), &data).is_err() {
+            warn!("Unable to save {name} to local storage");
+        }
+    }
+
+    /// Seconds since the Unix epoch.
+    pub fn now_timestamp() -> u64 {
+        (js_sys::Date::now() / 1000.0) as u64
+    }
+}
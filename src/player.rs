@@ -3,11 +3,13 @@ use bevy::prelude::*;
 use bevy_tnua::prelude::*;
 use bevy_tnua_avian3d::TnuaAvian3dSensorShape;
 use bevy_yoleck::{
-    prelude::*, vpeol::VpeolWillContainClickableChildren, vpeol_3d::Vpeol3dPosition,
+    prelude::*,
+    vpeol::VpeolWillContainClickableChildren,
+    vpeol_3d::{Vpeol3dPosition, Vpeol3dRotation},
 };
 use serde::{Deserialize, Serialize};
 
-use crate::util::affix_vpeol_y;
+use crate::util::{affix_vpeol_y, edit_vpeol_yaw};
 
 pub struct PlayerPlugin;
 
@@ -16,9 +18,11 @@ impl Plugin for PlayerPlugin {
         app.add_yoleck_entity_type({
             YoleckEntityType::new("Player")
                 .with::<Vpeol3dPosition>()
+                .with::<Vpeol3dRotation>()
                 .insert_on_init(|| IsPlayer)
         });
         affix_vpeol_y::<With<IsPlayer>>(app, 2.0);
+        edit_vpeol_yaw::<With<IsPlayer>>(app);
         app.add_systems(YoleckSchedule::Populate, populate_player);
     }
 }
@@ -0,0 +1,49 @@
+use avian3d::prelude::*;
+use bevy::prelude::*;
+use bevy_tnua::{builtins::TnuaBuiltinDash, prelude::*};
+
+use crate::{player::IsPlayer, player_controls::PotentialAttackTarget, During};
+
+pub struct PlayerAttackPlugin;
+
+impl Plugin for PlayerAttackPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<AttackHit>();
+        // Right after the physics step that started the collision, so that the hit lands on the
+        // next tick no matter how the ticks fall into frames
+        app.add_systems(
+            FixedPostUpdate,
+            detect_dash_hits
+                .after(PhysicsSet::StepSimulation)
+                .in_set(During::Gameplay),
+        );
+    }
+}
+
+#[derive(Event)]
+pub struct AttackHit {
+    pub attacker: Entity,
+    pub target: Entity,
+}
+
+fn detect_dash_hits(
+    mut collisions_reader: EventReader<CollisionStarted>,
+    players_query: Query<&TnuaController, With<IsPlayer>>,
+    targets_query: Query<(), With<PotentialAttackTarget>>,
+    mut hit_writer: EventWriter<AttackHit>,
+) {
+    for CollisionStarted(entity1, entity2) in collisions_reader.read() {
+        for (attacker, target) in [(*entity1, *entity2), (*entity2, *entity1)] {
+            let Ok(controller) = players_query.get(attacker) else {
+                continue;
+            };
+            if controller.action_name() != Some(TnuaBuiltinDash::NAME) {
+                continue;
+            }
+            if !targets_query.contains(target) {
+                continue;
+            }
+            hit_writer.send(AttackHit { attacker, target });
+        }
+    }
+}
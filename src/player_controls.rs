@@ -3,7 +3,11 @@ use bevy_tnua::{builtins::TnuaBuiltinDash, prelude::*};
 use leafwing_input_manager::prelude::*;
 use ordered_float::OrderedFloat;
 
-use crate::{player::IsPlayer, AppState, During};
+use crate::{
+    player::IsPlayer,
+    settings::{Binding, InputBindings, Settings},
+    AppState, During,
+};
 
 pub struct PlayerControlsPlugin;
 
@@ -11,15 +15,62 @@ impl Plugin for PlayerControlsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(InputManagerPlugin::<PlayerAction>::default());
         app.observe(add_controls_to_player);
-        app.add_systems(Update, apply_controls.in_set(During::Gameplay));
+        app.init_resource::<AimTarget>();
+        app.init_resource::<CameraYaw>();
+        app.init_resource::<AimAssist>();
+        // In `FixedUpdate`, so that the player moves the same regardless of the frame rate and
+        // replays play back the same way they were recorded
+        app.add_systems(
+            FixedUpdate,
+            (update_camera_yaw, update_aim_target, apply_controls)
+                .chain()
+                .in_set(During::Gameplay)
+                .in_set(ApplyControls)
+                .in_set(TnuaUserControlsSystemSet),
+        );
+        app.add_systems(
+            Update,
+            (apply_bindings_to_players, apply_aim_assist).run_if(resource_changed::<Settings>),
+        );
         app.add_systems(OnEnter(AppState::Game), release_jump_input);
+        app.add_systems(OnEnter(AppState::LoadLevel), reset_camera_yaw);
+        app.add_systems(OnExit(AppState::Editor), reset_camera_yaw);
+    }
+}
+
+/// Where the player's input turns into movement. Systems that feed the input should run before it.
+#[derive(SystemSet, Clone, PartialEq, Eq, Debug, Hash)]
+pub struct ApplyControls;
+
+/// In degrees per second.
+const CAMERA_ORBIT_SPEED: f32 = 120.0;
+
+/// How far, in radians, the player orbited the camera around the vertical axis. Movement is
+/// relative to this rather than to the camera's actual transform, because the camera is smoothed
+/// at the rendering frame rate.
+#[derive(Resource, Default)]
+pub struct CameraYaw(pub f32);
+
+/// The aim assist the dash targeting actually uses. Follows the settings, except in replays, which
+/// override it with the recorded value so that the dash picks the same targets.
+#[derive(Resource)]
+pub struct AimAssist(pub f32);
+
+impl Default for AimAssist {
+    fn default() -> Self {
+        Self(Settings::default().aim_assist)
     }
 }
 
 #[derive(Actionlike, PartialEq, Eq, Clone, Copy, Hash, Debug, Reflect)]
-enum PlayerAction {
+pub enum PlayerAction {
     Run,
     Jump,
+    Focus,
+    /// X orbits the camera around the player and Y zooms it in.
+    Camera,
+    /// Zooms in discrete steps, unlike the Y axis of [`PlayerAction::Camera`].
+    ZoomWheel,
 }
 
 #[derive(Component)]
@@ -27,26 +78,229 @@ pub struct PotentialAttackTarget {
     pub offset: Vec3,
 }
 
-fn add_controls_to_player(trigger: Trigger<OnInsert, IsPlayer>, mut commands: Commands) {
+/// The target a dash would go for right now. Only set while the player is airborne (and can
+/// therefore dash) or holding the focus button.
+#[derive(Resource, Default)]
+pub struct AimTarget {
+    pub entity: Option<Entity>,
+    /// From the player to where the dash should take them.
+    pub displacement: Vec3,
+}
+
+/// The narrowest cone targets are picked from, when aim assist is turned all the way down.
+const MIN_AIM_ANGLE: f32 = 10.0 * std::f32::consts::PI / 180.0;
+/// The widest cone targets are picked from, when aim assist is turned all the way up.
+const MAX_AIM_ANGLE: f32 = std::f32::consts::FRAC_PI_2;
+const MAX_AIM_DISTANCE: f32 = 10.0;
+
+impl From<Binding> for InputKind {
+    fn from(binding: Binding) -> Self {
+        match binding {
+            Binding::Key(key) => InputKind::PhysicalKey(key),
+            Binding::GamepadButton(button) => InputKind::GamepadButton(button),
+        }
+    }
+}
+
+/// Stands in for the directions of a dpad that have no bindings. No gamepad has a button with this
+/// ID, so it is never pressed.
+const UNBOUND_DIRECTION: InputKind = InputKind::GamepadButton(GamepadButtonType::Other(u8::MAX));
+
+/// Each direction may have a different number of bindings, so directions that run out of bindings
+/// keep using their last one. Directions without any bindings never move, but the other directions
+/// still work.
+fn insert_virtual_dpads(
+    input_map: &mut InputMap<PlayerAction>,
+    action: PlayerAction,
+    directions: [&Vec<Binding>; 4],
+) {
+    let num_dpads = directions.iter().map(|direction| direction.len()).max();
+    for i in 0..num_dpads.unwrap_or_default() {
+        let [up, down, left, right] = directions.map(|direction| {
+            direction
+                .get(i)
+                .or(direction.last())
+                .map(|binding| InputKind::from(*binding))
+                .unwrap_or(UNBOUND_DIRECTION)
+        });
+        input_map.insert(
+            action,
+            VirtualDPad {
+                up,
+                down,
+                left,
+                right,
+            },
+        );
+    }
+}
+
+fn build_input_map(bindings: &InputBindings) -> InputMap<PlayerAction> {
+    let mut input_map = InputMap::default();
+
+    insert_virtual_dpads(
+        &mut input_map,
+        PlayerAction::Run,
+        [
+            &bindings.run_up,
+            &bindings.run_down,
+            &bindings.run_left,
+            &bindings.run_right,
+        ],
+    );
+    input_map.insert(PlayerAction::Run, DualAxis::left_stick());
+
+    for binding in bindings.jump.iter() {
+        input_map.insert(PlayerAction::Jump, InputKind::from(*binding));
+    }
+
+    for binding in bindings.focus.iter() {
+        input_map.insert(PlayerAction::Focus, InputKind::from(*binding));
+    }
+
+    insert_virtual_dpads(
+        &mut input_map,
+        PlayerAction::Camera,
+        [
+            &bindings.zoom_in,
+            &bindings.zoom_out,
+            &bindings.camera_left,
+            &bindings.camera_right,
+        ],
+    );
+    input_map.insert(PlayerAction::Camera, DualAxis::right_stick());
+    input_map.insert(PlayerAction::ZoomWheel, SingleAxis::mouse_wheel_y());
+
+    input_map
+}
+
+fn add_controls_to_player(
+    trigger: Trigger<OnInsert, IsPlayer>,
+    settings: Res<Settings>,
+    mut commands: Commands,
+) {
     commands
         .entity(trigger.entity())
         .insert(InputManagerBundle::<PlayerAction> {
             action_state: Default::default(),
-            input_map: {
-                let mut input_map = InputMap::default();
+            input_map: build_input_map(&settings.bindings),
+        });
+}
+
+fn apply_bindings_to_players(
+    settings: Res<Settings>,
+    mut query: Query<&mut InputMap<PlayerAction>>,
+) {
+    for mut input_map in query.iter_mut() {
+        *input_map = build_input_map(&settings.bindings);
+    }
+}
 
-                input_map.insert(PlayerAction::Run, VirtualDPad::arrow_keys());
-                input_map.insert(PlayerAction::Run, VirtualDPad::wasd());
-                input_map.insert(PlayerAction::Run, VirtualDPad::dpad());
-                input_map.insert(PlayerAction::Run, DualAxis::left_stick());
+fn update_camera_yaw(
+    time: Res<Time>,
+    players_query: Query<&ActionState<PlayerAction>, With<IsPlayer>>,
+    mut camera_yaw: ResMut<CameraYaw>,
+) {
+    let Ok(input) = players_query.get_single() else {
+        return;
+    };
+    let orbit = input
+        .clamped_axis_pair(&PlayerAction::Camera)
+        .map(|axis_pair| axis_pair.x())
+        .unwrap_or_default();
+    camera_yaw.0 -= CAMERA_ORBIT_SPEED.to_radians() * orbit * time.delta_seconds();
+}
 
-                input_map.insert(PlayerAction::Jump, KeyCode::Space);
-                input_map.insert(PlayerAction::Jump, KeyCode::KeyJ);
-                input_map.insert(PlayerAction::Jump, GamepadButtonType::South);
+fn apply_aim_assist(settings: Res<Settings>, mut aim_assist: ResMut<AimAssist>) {
+    aim_assist.0 = settings.aim_assist.clamp(0.0, 1.0);
+}
 
-                input_map
+fn reset_camera_yaw(mut camera_yaw: ResMut<CameraYaw>) {
+    camera_yaw.0 = 0.0;
+}
+
+fn desired_velocity(input: &ActionState<PlayerAction>, camera_rotation: Quat) -> Vec3 {
+    input
+        .clamped_axis_pair(&PlayerAction::Run)
+        .map(|axis_pair| camera_rotation * Vec3::new(axis_pair.x(), 0.0, -axis_pair.y()))
+        .unwrap_or_default()
+}
+
+/// The direction the player would dash in: where they are running, or where they are facing if
+/// they are standing still.
+fn attack_direction(
+    input: &ActionState<PlayerAction>,
+    player_transform: &GlobalTransform,
+    camera_rotation: Quat,
+) -> Dir3 {
+    Dir3::new(desired_velocity(input, camera_rotation))
+        .unwrap_or_else(|_| player_transform.forward())
+}
+
+/// Picks the target a dash in `attack_direction` would go for. Returns the target entity and the
+/// displacement to it.
+fn choose_attack_target<'a>(
+    player_position: Vec3,
+    attack_direction: Dir3,
+    max_angle: f32,
+    targets: impl Iterator<Item = (Entity, &'a GlobalTransform, &'a PotentialAttackTarget)>,
+) -> Option<(Entity, Vec3)> {
+    targets
+        .filter_map(
+            |(target_entity, target_transform, PotentialAttackTarget { offset })| {
+                let vec_to_target = target_transform.translation() - player_position;
+                let distance_sq = vec_to_target.length_squared();
+                if distance_sq < 0.2f32.powi(2) {
+                    return None;
+                }
+                if MAX_AIM_DISTANCE.powi(2) < distance_sq {
+                    return None;
+                }
+                let angle = attack_direction.angle_between(vec_to_target).abs();
+                if max_angle < angle {
+                    return None;
+                }
+                Some((distance_sq * angle, target_entity, vec_to_target + *offset))
             },
+        )
+        .min_by_key(|(score, _, _)| OrderedFloat(*score))
+        .map(|(_, target_entity, vec_to_target)| (target_entity, vec_to_target))
+}
+
+fn update_aim_target(
+    mut aim_target: ResMut<AimTarget>,
+    aim_assist: Res<AimAssist>,
+    players_query: Query<(
+        &ActionState<PlayerAction>,
+        &TnuaController,
+        &GlobalTransform,
+    )>,
+    attack_targets_query: Query<(Entity, &GlobalTransform, &PotentialAttackTarget)>,
+    camera_yaw: Res<CameraYaw>,
+) {
+    let target = players_query
+        .get_single()
+        .ok()
+        .filter(|(input, controller, _)| {
+            controller.is_airborne().is_ok_and(|airborne| airborne)
+                || input.pressed(&PlayerAction::Focus)
+        })
+        .and_then(|(input, _, player_transform)| {
+            let camera_rotation = Quat::from_rotation_y(camera_yaw.0);
+            choose_attack_target(
+                player_transform.translation(),
+                attack_direction(input, player_transform, camera_rotation),
+                MIN_AIM_ANGLE + (MAX_AIM_ANGLE - MIN_AIM_ANGLE) * aim_assist.0,
+                attack_targets_query.iter(),
+            )
         });
+    *aim_target = match target {
+        Some((entity, displacement)) => AimTarget {
+            entity: Some(entity),
+            displacement,
+        },
+        None => AimTarget::default(),
+    };
 }
 
 fn apply_controls(
@@ -55,15 +309,14 @@ fn apply_controls(
         &mut TnuaController,
         &GlobalTransform,
     )>,
-    attack_targets_query: Query<(&GlobalTransform, &PotentialAttackTarget)>,
+    aim_target: Res<AimTarget>,
+    camera_yaw: Res<CameraYaw>,
 ) {
+    let camera_rotation = Quat::from_rotation_y(camera_yaw.0);
     for (input, mut controller, player_transform) in query.iter_mut() {
         let controller = controller.as_mut();
 
-        let desired_velocity = input
-            .clamped_axis_pair(&PlayerAction::Run)
-            .map(|axis_pair| Vec3::new(axis_pair.x(), 0.0, -axis_pair.y()))
-            .unwrap_or_default();
+        let desired_velocity = desired_velocity(input, camera_rotation);
         let desired_direction = Dir3::new(desired_velocity).ok();
 
         controller.basis(TnuaBuiltinWalk {
@@ -96,27 +349,13 @@ fn apply_controls(
             if !controller.is_airborne().is_ok_and(|airborne| airborne) {
                 break 'attack_target None;
             }
-            let player_position = player_transform.translation();
-            let attack_direction = desired_direction.unwrap_or_else(|| player_transform.forward());
-            attack_targets_query
-                .iter()
-                .filter_map(|(target_translation, PotentialAttackTarget { offset })| {
-                    let vec_to_target = target_translation.translation() - player_position;
-                    let distance_sq = vec_to_target.length_squared();
-                    if distance_sq < 0.2f32.powi(2) {
-                        return None;
-                    }
-                    if 10.0f32.powi(2) < distance_sq {
-                        return None;
-                    }
-                    let angle = attack_direction.angle_between(vec_to_target).abs();
-                    if std::f32::consts::FRAC_PI_2 < angle {
-                        return None;
-                    }
-                    Some((distance_sq * angle, vec_to_target + *offset))
-                })
-                .min_by_key(|(score, _)| OrderedFloat(*score))
-                .map(|(_, v)| (attack_direction, v))
+            if aim_target.entity.is_none() {
+                break 'attack_target None;
+            }
+            Some((
+                attack_direction(input, player_transform, camera_rotation),
+                aim_target.displacement,
+            ))
         };
 
         if let Some((attack_direction, vec_to_target)) = attack_target {
@@ -0,0 +1,255 @@
+//! Recording the player's inputs so that a run can be played back.
+//!
+//! A replay only covers the first level played after startup, because that is the only point
+//! where the state of the RNG is known.
+
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+use leafwing_input_manager::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    loading::CurrentLevel,
+    player::IsPlayer,
+    player_controls::{AimAssist, ApplyControls, PlayerAction},
+    AppState,
+};
+
+#[derive(Clone)]
+pub enum ReplayMode {
+    Record { path: PathBuf, seed: u64 },
+    Play(Replay),
+}
+
+impl ReplayMode {
+    pub fn seed(&self) -> u64 {
+        match self {
+            ReplayMode::Record { seed, .. } => *seed,
+            ReplayMode::Play(replay) => replay.seed,
+        }
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Replay {
+    pub seed: u64,
+    pub level: String,
+    frames: Vec<ReplayFrame>,
+}
+
+impl Replay {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let data = std::fs::read_to_string(path).map_err(|err| err.to_string())?;
+        serde_json::from_str(&data).map_err(|err| err.to_string())
+    }
+}
+
+/// The player's input during a single `FixedUpdate` tick.
+#[derive(Clone, Serialize, Deserialize)]
+struct ReplayFrame {
+    run: [f32; 2],
+    /// `None` when the jump button is not pressed.
+    jump: Option<f32>,
+    #[serde(default)]
+    focus: bool,
+    /// Movement is relative to the camera, so its rotation affects the gameplay.
+    #[serde(default)]
+    camera: [f32; 2],
+    /// It changes which dweeb the dash goes for. Only recorded on the first frame and when it
+    /// changes, and older replays (from before it could be changed) don't have it at all.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    aim_assist: Option<f32>,
+}
+
+pub struct ReplayPlugin {
+    pub mode: ReplayMode,
+}
+
+impl Plugin for ReplayPlugin {
+    fn build(&self, app: &mut App) {
+        match &self.mode {
+            ReplayMode::Record { path, seed } => {
+                app.insert_resource(ReplayRecorder {
+                    path: path.clone(),
+                    replay: Replay {
+                        seed: *seed,
+                        level: Default::default(),
+                        frames: Default::default(),
+                    },
+                    is_done: false,
+                    last_aim_assist: None,
+                });
+                app.add_systems(OnEnter(AppState::LoadLevel), start_recording);
+                app.add_systems(
+                    FixedUpdate,
+                    record_inputs
+                        .before(ApplyControls)
+                        .run_if(in_state(AppState::Game)),
+                );
+                app.add_systems(OnEnter(AppState::LevelCompleted), save_recording);
+                app.add_systems(OnEnter(AppState::GameOver), save_recording);
+                // Quitting in the middle of the run (from the menu or by closing the window)
+                app.add_systems(Last, save_recording.run_if(on_event::<AppExit>()));
+            }
+            ReplayMode::Play(replay) => {
+                app.insert_resource(ReplayPlayer {
+                    replay: replay.clone(),
+                    next_frame: 0,
+                    aim_assist: AimAssist::default().0,
+                });
+                app.add_systems(
+                    FixedUpdate,
+                    play_inputs
+                        .before(ApplyControls)
+                        .run_if(in_state(AppState::Game)),
+                );
+            }
+        }
+    }
+}
+
+#[derive(Resource)]
+struct ReplayRecorder {
+    path: PathBuf,
+    replay: Replay,
+    is_done: bool,
+    last_aim_assist: Option<f32>,
+}
+
+#[derive(Resource)]
+struct ReplayPlayer {
+    replay: Replay,
+    next_frame: usize,
+    /// The last recorded aim assist.
+    aim_assist: f32,
+}
+
+fn start_recording(mut recorder: ResMut<ReplayRecorder>, current_level: Res<CurrentLevel>) {
+    if !recorder.replay.frames.is_empty() {
+        // Restarting the level would not restart the RNG, so only the first run is recorded.
+        save_recording(recorder);
+        return;
+    }
+    recorder.replay.level = current_level.0.clone();
+}
+
+fn record_inputs(
+    mut recorder: ResMut<ReplayRecorder>,
+    query: Query<&ActionState<PlayerAction>, With<IsPlayer>>,
+    aim_assist: Res<AimAssist>,
+) {
+    if recorder.is_done {
+        return;
+    }
+    let Ok(input) = query.get_single() else {
+        return;
+    };
+    let run = input
+        .clamped_axis_pair(&PlayerAction::Run)
+        .map(|axis_pair| [axis_pair.x(), axis_pair.y()])
+        .unwrap_or_default();
+    let jump = input
+        .pressed(&PlayerAction::Jump)
+        .then(|| input.clamped_value(&PlayerAction::Jump));
+    let focus = input.pressed(&PlayerAction::Focus);
+    let camera = input
+        .clamped_axis_pair(&PlayerAction::Camera)
+        .map(|axis_pair| [axis_pair.x(), axis_pair.y()])
+        .unwrap_or_default();
+    let aim_assist = (recorder.last_aim_assist != Some(aim_assist.0)).then_some(aim_assist.0);
+    if aim_assist.is_some() {
+        recorder.last_aim_assist = aim_assist;
+    }
+    recorder.replay.frames.push(ReplayFrame {
+        run,
+        jump,
+        focus,
+        camera,
+        aim_assist,
+    });
+}
+
+fn save_recording(mut recorder: ResMut<ReplayRecorder>) {
+    if recorder.is_done {
+        return;
+    }
+    recorder.is_done = true;
+    let result = serde_json::to_string(&recorder.replay)
+        .map_err(|err| err.to_string())
+        .and_then(|data| std::fs::write(&recorder.path, data).map_err(|err| err.to_string()));
+    match result {
+        Ok(()) => info!("Replay saved to {}", recorder.path.display()),
+        Err(err) => error!(
+            "Unable to save replay to {}: {err}",
+            recorder.path.display()
+        ),
+    }
+}
+
+fn play_inputs(
+    mut player: ResMut<ReplayPlayer>,
+    mut aim_assist: ResMut<AimAssist>,
+    mut query: Query<
+        (
+            Entity,
+            &mut ActionState<PlayerAction>,
+            Has<InputMap<PlayerAction>>,
+        ),
+        With<IsPlayer>,
+    >,
+    mut commands: Commands,
+) {
+    let Ok((player_entity, mut input, has_input_map)) = query.get_single_mut() else {
+        return;
+    };
+    if has_input_map {
+        // Otherwise the actual input devices would override the replay
+        commands
+            .entity(player_entity)
+            .remove::<InputMap<PlayerAction>>();
+    }
+    let frame = player.replay.frames.get(player.next_frame).cloned();
+    player.next_frame += 1;
+    let ReplayFrame {
+        run,
+        jump,
+        focus,
+        camera,
+        aim_assist: recorded_aim_assist,
+    } = frame.unwrap_or(ReplayFrame {
+        run: [0.0, 0.0],
+        jump: None,
+        focus: false,
+        camera: [0.0, 0.0],
+        aim_assist: None,
+    });
+
+    if let Some(recorded_aim_assist) = recorded_aim_assist {
+        player.aim_assist = recorded_aim_assist;
+    }
+    // Set on every tick, because changing the settings would override it
+    aim_assist.0 = player.aim_assist;
+
+    input
+        .action_data_mut_or_default(&PlayerAction::Run)
+        .axis_pair = Some(DualAxisData::from_xy(Vec2::from_array(run)));
+    input
+        .action_data_mut_or_default(&PlayerAction::Camera)
+        .axis_pair = Some(DualAxisData::from_xy(Vec2::from_array(camera)));
+    if let Some(jump) = jump {
+        if !input.pressed(&PlayerAction::Jump) {
+            input.press(&PlayerAction::Jump);
+        }
+        input.action_data_mut_or_default(&PlayerAction::Jump).value = jump;
+    } else if input.pressed(&PlayerAction::Jump) {
+        input.release(&PlayerAction::Jump);
+    }
+    if focus != input.pressed(&PlayerAction::Focus) {
+        if focus {
+            input.press(&PlayerAction::Focus);
+        } else {
+            input.release(&PlayerAction::Focus);
+        }
+    }
+}
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+use bevy_egui::egui;
+use bevy_yoleck::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub struct ReservationPlugin;
+
+impl Plugin for ReservationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_edit_system(edit_capacity);
+    }
+}
+
+/// How many dweebs can use a bed or a desk at the same time.
+#[derive(Clone, Copy, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(default)]
+pub struct Capacity {
+    pub dweebs: usize,
+}
+
+impl Default for Capacity {
+    fn default() -> Self {
+        Self { dweebs: 1 }
+    }
+}
+
+/// The dweebs that are currently walking to or using a bed or a desk.
+#[derive(Component, Default, Debug)]
+pub struct Reservations {
+    dweebs: Vec<Entity>,
+}
+
+impl Reservations {
+    pub fn is_held_by(&self, dweeb: Entity) -> bool {
+        self.dweebs.contains(&dweeb)
+    }
+
+    pub fn is_available_for(&self, capacity: &Capacity, dweeb: Entity) -> bool {
+        self.is_held_by(dweeb) || self.dweebs.len() < capacity.dweebs
+    }
+
+    /// Returns `false` if all the slots are already reserved by other dweebs.
+    pub fn claim(&mut self, capacity: &Capacity, dweeb: Entity) -> bool {
+        if self.is_held_by(dweeb) {
+            return true;
+        }
+        if capacity.dweebs <= self.dweebs.len() {
+            return false;
+        }
+        self.dweebs.push(dweeb);
+        true
+    }
+
+    pub fn release_unless(&mut self, mut keep: impl FnMut(Entity) -> bool) {
+        self.dweebs.retain(|dweeb| keep(*dweeb));
+    }
+}
+
+fn edit_capacity(mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut Capacity>) {
+    let Ok(mut capacity) = edit.get_single_mut() else {
+        return;
+    };
+    ui.add(egui::Slider::new(&mut capacity.dweebs, 1..=4).text("Capacity"));
+}
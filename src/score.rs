@@ -3,17 +3,26 @@ use std::time::Duration;
 use bevy::prelude::*;
 use bevy_egui::{egui, EguiContexts};
 
-use crate::{AppState, During};
+use crate::{gameplay_time::GameplayTime, level_rules::LevelRules, AppState, DisplayUi, During};
 
-pub struct ScorePlugin;
+/// How long after an idea scribed by a dweeb the player woke from REM sleep the next one has to
+/// come in order to continue the streak.
+const COMBO_WINDOW_SECS: f32 = 15.0;
+const MAX_COMBO_MULTIPLIER: usize = 5;
+
+pub struct ScorePlugin {
+    /// When `false` the timer still runs, but running out of time does not end the game.
+    pub has_time_limit: bool,
+}
 
 impl Plugin for ScorePlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(GameData::init());
+        app.insert_resource(GameData::init(&LevelRules::default()));
         app.add_systems(
             Update,
-            (
-                display_game_data.run_if(|app_state: Res<State<AppState>>| match app_state.get() {
+            display_game_data
+                .in_set(DisplayUi)
+                .run_if(|app_state: Res<State<AppState>>| match app_state.get() {
                     AppState::MainMenu => false,
                     AppState::PauseMenu => true,
                     AppState::LoadLevel => false,
@@ -22,27 +31,84 @@ impl Plugin for ScorePlugin {
                     AppState::LevelCompleted => true,
                     AppState::GameOver => true,
                 }),
+        );
+        let has_time_limit = self.has_time_limit;
+        // In `FixedUpdate`, so that time-outs and penalties happen on the same tick in replays
+        app.add_systems(
+            FixedUpdate,
+            (
+                apply_level_rules,
                 handle_score_event,
-                update_time.in_set(During::Gameplay),
-            ),
+                handle_time_penalty,
+                (
+                    update_time,
+                    check_time_out.run_if(move || has_time_limit),
+                    check_level_completed,
+                )
+                    .chain()
+                    .in_set(During::Gameplay),
+            )
+                .chain(),
         );
         app.add_systems(OnEnter(AppState::LoadLevel), restart_score_and_timer);
         app.add_event::<IncreaseScore>();
+        app.add_event::<TimePenalty>();
     }
 }
 
 #[derive(Resource)]
 pub struct GameData {
     score: usize,
+    target_score: usize,
     time: Timer,
+    ideas_scribed: usize,
+    /// Consecutive ideas scribed by dweebs the player woke from REM sleep.
+    streak: usize,
+    combo_window: Timer,
+    game_over_reason: Option<GameOverReason>,
+    /// For showing the last penalty in the HUD.
+    penalty_feedback: Option<PenaltyFeedback>,
+}
+
+struct PenaltyFeedback {
+    secs: f32,
+    timer: Timer,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameOverReason {
+    TimeOut,
+    PlayerFell,
+    DweebKnockedOff,
+    TooManyStartled,
+}
+
+impl GameOverReason {
+    pub fn description(&self) -> &'static str {
+        match self {
+            GameOverReason::TimeOut => "time out",
+            GameOverReason::PlayerFell => "player fell",
+            GameOverReason::DweebKnockedOff => "dweeb knocked off",
+            GameOverReason::TooManyStartled => "too many dweebs startled",
+        }
+    }
 }
 
 impl GameData {
     // Don't use Default so that it can be kept private
-    fn init() -> Self {
+    fn init(level_rules: &LevelRules) -> Self {
         Self {
             score: 0,
-            time: Timer::new(Duration::from_secs(60), TimerMode::Once),
+            target_score: level_rules.target_score,
+            time: Timer::new(
+                Duration::from_secs_f32(level_rules.time_limit),
+                TimerMode::Once,
+            ),
+            ideas_scribed: 0,
+            streak: 0,
+            combo_window: Timer::from_seconds(COMBO_WINDOW_SECS, TimerMode::Once),
+            game_over_reason: None,
+            penalty_feedback: None,
         }
     }
 
@@ -50,23 +116,86 @@ impl GameData {
         self.score
     }
 
-    pub fn is_finished(&self) -> bool {
-        self.time.finished()
+    pub fn target_score(&self) -> usize {
+        self.target_score
+    }
+
+    pub fn ideas_scribed(&self) -> usize {
+        self.ideas_scribed
+    }
+
+    pub fn streak(&self) -> usize {
+        self.streak
+    }
+
+    pub fn combo_multiplier(&self) -> usize {
+        self.streak.clamp(1, MAX_COMBO_MULTIPLIER)
+    }
+
+    pub fn is_target_reached(&self) -> bool {
+        self.target_score <= self.score
+    }
+
+    pub fn elapsed_secs(&self) -> f32 {
+        self.time.elapsed_secs()
+    }
+
+    pub fn game_over_reason(&self) -> Option<GameOverReason> {
+        self.game_over_reason
+    }
+
+    /// Only the first reason is kept, in case several failures happen on the same frame.
+    pub fn set_game_over_reason(&mut self, reason: GameOverReason) {
+        self.game_over_reason.get_or_insert(reason);
     }
 }
 
 #[derive(Event)]
-pub struct IncreaseScore;
+pub struct IncreaseScore {
+    pub dweeb: Entity,
+    pub desk: Entity,
+    /// `false` if the dweeb woke up on its own.
+    pub woken_by_player: bool,
+}
+
+/// Takes time off the clock.
+#[derive(Event)]
+pub struct TimePenalty {
+    pub secs: f32,
+}
 
 fn display_game_data(mut egui_contexts: EguiContexts, game_data: Res<GameData>) {
     let ctx = egui_contexts.ctx_mut();
     let panel = egui::Area::new("display-score".into()).fixed_pos([0.0, 0.0]);
     panel.show(ctx, |ui| {
         ui.label(
-            egui::RichText::new(format!("Ideas Scribed: {}", game_data.score))
-                .strong()
-                .size(36.0),
+            egui::RichText::new(format!(
+                "Score: {}/{}",
+                game_data.score, game_data.target_score
+            ))
+            .strong()
+            .size(36.0),
         );
+        if 0 < game_data.streak {
+            ui.add(
+                egui::ProgressBar::new(
+                    game_data.combo_window.remaining_secs()
+                        / game_data.combo_window.duration().as_secs_f32(),
+                )
+                .desired_width(150.0)
+                .fill(egui::Color32::LIGHT_BLUE)
+                .text(
+                    egui::RichText::new(format!(
+                        "Streak: {} (x{})",
+                        game_data.streak,
+                        game_data.combo_multiplier()
+                    ))
+                    .strong()
+                    .color(egui::Color32::DARK_BLUE)
+                    .size(24.0),
+                ),
+            );
+        }
         let remaining_time = game_data.time.remaining();
         ui.add(
             egui::ProgressBar::new(
@@ -81,25 +210,99 @@ fn display_game_data(mut egui_contexts: EguiContexts, game_data: Res<GameData>)
                     .size(24.0),
             ),
         );
+        if let Some(penalty_feedback) = game_data.penalty_feedback.as_ref() {
+            if !penalty_feedback.timer.finished() {
+                ui.label(
+                    egui::RichText::new(format!(
+                        "-{:.0}s: woke a dweeb from non-REM sleep!",
+                        penalty_feedback.secs
+                    ))
+                    .strong()
+                    .color(egui::Color32::RED)
+                    .size(24.0),
+                );
+            }
+        }
     });
 }
 
 fn handle_score_event(mut reader: EventReader<IncreaseScore>, mut game_data: ResMut<GameData>) {
-    for _ in reader.read() {
-        game_data.score += 1;
+    for IncreaseScore {
+        woken_by_player, ..
+    } in reader.read()
+    {
+        game_data.ideas_scribed += 1;
+        if *woken_by_player {
+            game_data.streak += 1;
+            game_data.combo_window.reset();
+            game_data.score += game_data.combo_multiplier();
+        } else {
+            game_data.score += 1;
+        }
     }
 }
 
-fn update_time(
-    time: Res<Time>,
-    mut game_data: ResMut<GameData>,
-    mut next_state: ResMut<NextState<AppState>>,
-) {
-    if game_data.time.tick(time.delta()).finished() {
+fn handle_time_penalty(mut reader: EventReader<TimePenalty>, mut game_data: ResMut<GameData>) {
+    for TimePenalty { secs } in reader.read() {
+        game_data.time.tick(Duration::from_secs_f32(*secs));
+        game_data.streak = 0;
+        // Penalties that come in quick succession are shown together
+        let shown_secs = match game_data.penalty_feedback.as_ref() {
+            Some(penalty_feedback) if !penalty_feedback.timer.finished() => penalty_feedback.secs,
+            _ => 0.0,
+        };
+        game_data.penalty_feedback = Some(PenaltyFeedback {
+            secs: shown_secs + secs,
+            timer: Timer::from_seconds(2.0, TimerMode::Once),
+        });
+    }
+}
+
+fn update_time(time: GameplayTime, mut game_data: ResMut<GameData>) {
+    if let Some(penalty_feedback) = game_data.penalty_feedback.as_mut() {
+        penalty_feedback.timer.tick(time.delta());
+    }
+    if game_data.combo_window.tick(time.delta()).just_finished() {
+        game_data.streak = 0;
+    }
+    game_data.time.tick(time.delta());
+}
+
+fn check_time_out(mut game_data: ResMut<GameData>, mut next_state: ResMut<NextState<AppState>>) {
+    if game_data.time.finished() {
+        game_data.set_game_over_reason(GameOverReason::TimeOut);
         next_state.set(AppState::GameOver);
     }
 }
 
+fn check_level_completed(game_data: Res<GameData>, mut next_state: ResMut<NextState<AppState>>) {
+    // The timer may have run out on the same tick the target was reached
+    if game_data.game_over_reason().is_some() {
+        return;
+    }
+    if game_data.is_target_reached() {
+        next_state.set(AppState::LevelCompleted);
+    }
+}
+
 fn restart_score_and_timer(mut game_data: ResMut<GameData>) {
-    *game_data = GameData::init();
+    *game_data = GameData::init(&LevelRules::default());
+}
+
+/// The level entities (including its `LevelRules`) are only loaded after `restart_score_and_timer`
+/// runs, so the rules need to be applied when they appear. Only the parts of `GameData` that come
+/// from the rules are updated, so that the progress of a running game is kept.
+fn apply_level_rules(
+    changed_query: Query<(), Changed<LevelRules>>,
+    level_rules_query: Query<(Entity, &LevelRules)>,
+    mut game_data: ResMut<GameData>,
+) {
+    if changed_query.is_empty() {
+        return;
+    }
+    let level_rules = LevelRules::from_query(&level_rules_query);
+    game_data.target_score = level_rules.target_score;
+    game_data
+        .time
+        .set_duration(Duration::from_secs_f32(level_rules.time_limit));
 }
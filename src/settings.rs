@@ -0,0 +1,245 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use bevy_egui_kbgp::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{persistence, ActionForKbgp};
+
+pub struct SettingsPlugin;
+
+impl Plugin for SettingsPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(persistence::load::<Settings>("settings").unwrap_or_default());
+        app.add_systems(
+            Update,
+            (
+                apply_kbgp_bindings.run_if(resource_changed::<Settings>),
+                save_settings
+                    .run_if(resource_changed::<Settings>.and_then(not(resource_added::<Settings>))),
+            ),
+        );
+    }
+}
+
+#[derive(Resource, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Settings {
+    pub bindings: InputBindings,
+    /// Between 0 and 1. Higher values let the dash pick targets further from where the player is
+    /// aiming.
+    pub aim_assist: f32,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Self {
+            bindings: Default::default(),
+            aim_assist: 1.0,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Debug, Serialize, Deserialize)]
+pub enum Binding {
+    Key(KeyCode),
+    GamepadButton(GamepadButtonType),
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Binding::Key(key) => write!(f, "{key:?}"),
+            Binding::GamepadButton(button) => write!(f, "Gamepad {button:?}"),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum BindableAction {
+    RunUp,
+    RunDown,
+    RunLeft,
+    RunRight,
+    Jump,
+    Focus,
+    CameraLeft,
+    CameraRight,
+    ZoomIn,
+    ZoomOut,
+    Menu,
+    RestartLevel,
+}
+
+impl BindableAction {
+    pub const ALL: [Self; 12] = [
+        Self::RunUp,
+        Self::RunDown,
+        Self::RunLeft,
+        Self::RunRight,
+        Self::Jump,
+        Self::Focus,
+        Self::CameraLeft,
+        Self::CameraRight,
+        Self::ZoomIn,
+        Self::ZoomOut,
+        Self::Menu,
+        Self::RestartLevel,
+    ];
+}
+
+impl fmt::Display for BindableAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            BindableAction::RunUp => "Run Up",
+            BindableAction::RunDown => "Run Down",
+            BindableAction::RunLeft => "Run Left",
+            BindableAction::RunRight => "Run Right",
+            BindableAction::Jump => "Jump",
+            BindableAction::Focus => "Focus",
+            BindableAction::CameraLeft => "Camera Left",
+            BindableAction::CameraRight => "Camera Right",
+            BindableAction::ZoomIn => "Zoom In",
+            BindableAction::ZoomOut => "Zoom Out",
+            BindableAction::Menu => "Menu",
+            BindableAction::RestartLevel => "Restart Level",
+        })
+    }
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InputBindings {
+    pub run_up: Vec<Binding>,
+    pub run_down: Vec<Binding>,
+    pub run_left: Vec<Binding>,
+    pub run_right: Vec<Binding>,
+    pub jump: Vec<Binding>,
+    pub focus: Vec<Binding>,
+    pub camera_left: Vec<Binding>,
+    pub camera_right: Vec<Binding>,
+    pub zoom_in: Vec<Binding>,
+    pub zoom_out: Vec<Binding>,
+    pub menu: Vec<Binding>,
+    pub restart_level: Vec<Binding>,
+}
+
+impl Default for InputBindings {
+    fn default() -> Self {
+        use Binding::{GamepadButton as G, Key as K};
+        Self {
+            run_up: vec![
+                K(KeyCode::ArrowUp),
+                K(KeyCode::KeyW),
+                G(GamepadButtonType::DPadUp),
+            ],
+            run_down: vec![
+                K(KeyCode::ArrowDown),
+                K(KeyCode::KeyS),
+                G(GamepadButtonType::DPadDown),
+            ],
+            run_left: vec![
+                K(KeyCode::ArrowLeft),
+                K(KeyCode::KeyA),
+                G(GamepadButtonType::DPadLeft),
+            ],
+            run_right: vec![
+                K(KeyCode::ArrowRight),
+                K(KeyCode::KeyD),
+                G(GamepadButtonType::DPadRight),
+            ],
+            jump: vec![
+                K(KeyCode::Space),
+                K(KeyCode::KeyJ),
+                G(GamepadButtonType::South),
+            ],
+            focus: vec![
+                K(KeyCode::ShiftLeft),
+                K(KeyCode::KeyK),
+                G(GamepadButtonType::LeftTrigger2),
+            ],
+            camera_left: vec![K(KeyCode::KeyQ)],
+            camera_right: vec![K(KeyCode::KeyE)],
+            zoom_in: vec![K(KeyCode::KeyR)],
+            zoom_out: vec![K(KeyCode::KeyF)],
+            menu: vec![K(KeyCode::Escape), G(GamepadButtonType::Start)],
+            restart_level: vec![K(KeyCode::Backspace), G(GamepadButtonType::Select)],
+        }
+    }
+}
+
+impl InputBindings {
+    pub fn get(&self, action: BindableAction) -> &[Binding] {
+        match action {
+            BindableAction::RunUp => &self.run_up,
+            BindableAction::RunDown => &self.run_down,
+            BindableAction::RunLeft => &self.run_left,
+            BindableAction::RunRight => &self.run_right,
+            BindableAction::Jump => &self.jump,
+            BindableAction::Focus => &self.focus,
+            BindableAction::CameraLeft => &self.camera_left,
+            BindableAction::CameraRight => &self.camera_right,
+            BindableAction::ZoomIn => &self.zoom_in,
+            BindableAction::ZoomOut => &self.zoom_out,
+            BindableAction::Menu => &self.menu,
+            BindableAction::RestartLevel => &self.restart_level,
+        }
+    }
+
+    pub fn get_mut(&mut self, action: BindableAction) -> &mut Vec<Binding> {
+        match action {
+            BindableAction::RunUp => &mut self.run_up,
+            BindableAction::RunDown => &mut self.run_down,
+            BindableAction::RunLeft => &mut self.run_left,
+            BindableAction::RunRight => &mut self.run_right,
+            BindableAction::Jump => &mut self.jump,
+            BindableAction::Focus => &mut self.focus,
+            BindableAction::CameraLeft => &mut self.camera_left,
+            BindableAction::CameraRight => &mut self.camera_right,
+            BindableAction::ZoomIn => &mut self.zoom_in,
+            BindableAction::ZoomOut => &mut self.zoom_out,
+            BindableAction::Menu => &mut self.menu,
+            BindableAction::RestartLevel => &mut self.restart_level,
+        }
+    }
+
+    fn kbgp_nav_bindings(&self) -> KbgpNavBindings {
+        let mut bindings = KbgpNavBindings::default();
+        for (action, command) in [
+            (BindableAction::RunUp, KbgpNavCommand::Up),
+            (BindableAction::RunDown, KbgpNavCommand::Down),
+            (BindableAction::RunLeft, KbgpNavCommand::Left),
+            (BindableAction::RunRight, KbgpNavCommand::Right),
+            (BindableAction::Jump, KbgpNavCommand::Click),
+            (
+                BindableAction::Menu,
+                KbgpNavCommand::user(ActionForKbgp::Menu),
+            ),
+            (
+                BindableAction::RestartLevel,
+                KbgpNavCommand::user(ActionForKbgp::RestartLevel),
+            ),
+        ] {
+            for binding in self.get(action) {
+                bindings = match binding {
+                    Binding::Key(key) => bindings.with_key(*key, command.clone()),
+                    Binding::GamepadButton(button) => {
+                        bindings.with_gamepad_button(*button, command.clone())
+                    }
+                };
+            }
+        }
+        bindings
+    }
+}
+
+fn apply_kbgp_bindings(settings: Res<Settings>, kbgp_settings: Option<ResMut<KbgpSettings>>) {
+    let Some(mut kbgp_settings) = kbgp_settings else {
+        return;
+    };
+    kbgp_settings.bindings = settings.bindings.kbgp_nav_bindings();
+}
+
+fn save_settings(settings: Res<Settings>) {
+    persistence::save("settings", settings.as_ref());
+}
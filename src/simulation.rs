@@ -0,0 +1,183 @@
+//! Running a level without rendering or a player, to gather statistics about the dweebs' AI.
+
+use bevy::{prelude::*, utils::HashMap};
+use bevy_yoetz::prelude::*;
+
+use crate::{
+    bed::Bed,
+    desk::Desk,
+    dweeb::Dweeb,
+    dweeb_behavior::{describe_behavior, BehaviorDescription},
+    player::IsPlayer,
+    score::{GameData, IncreaseScore},
+    AppState,
+};
+
+const BEHAVIOR_NAMES: [&str; 7] = [
+    "Idle",
+    "WalkToBed",
+    "JumpOnBed",
+    "Sleep",
+    "Startled",
+    "WalkToDesk",
+    "Scribe",
+];
+
+pub struct SimulationPlugin {
+    pub ticks: u64,
+}
+
+impl Plugin for SimulationPlugin {
+    fn build(&self, app: &mut App) {
+        app.insert_resource(SimulationStats {
+            ticks_left: self.ticks,
+            ..Default::default()
+        });
+        app.observe(remove_player);
+        app.add_systems(
+            FixedUpdate,
+            collect_behavior_stats
+                .after(YoetzSystemSet::Act)
+                .run_if(in_state(AppState::Game)),
+        );
+        app.add_systems(
+            OnEnter(AppState::LevelCompleted),
+            |mut stats: ResMut<SimulationStats>| {
+                stats.end_reason = Some("level completed");
+            },
+        );
+        app.add_systems(
+            OnEnter(AppState::GameOver),
+            |mut stats: ResMut<SimulationStats>, game_data: Res<GameData>| {
+                stats.end_reason = Some(
+                    game_data
+                        .game_over_reason()
+                        .map_or("game over", |reason| reason.description()),
+                );
+            },
+        );
+        app.add_systems(
+            Update,
+            report_and_exit.run_if(|stats: Res<SimulationStats>| stats.end_reason.is_some()),
+        );
+    }
+}
+
+#[derive(Resource, Default)]
+struct SimulationStats {
+    ticks_left: u64,
+    ticks_simulated: u64,
+    end_reason: Option<&'static str>,
+    behavior_ticks: HashMap<Entity, HashMap<&'static str, u64>>,
+    occupying: HashMap<Entity, Option<Entity>>,
+    furniture_uses: HashMap<Entity, usize>,
+    /// Both per dweeb and per desk.
+    ideas: HashMap<Entity, usize>,
+}
+
+/// The level's player would just stand there, getting in the way of the dweebs.
+fn remove_player(trigger: Trigger<OnAdd, IsPlayer>, mut commands: Commands) {
+    commands.entity(trigger.entity()).despawn_recursive();
+}
+
+fn collect_behavior_stats(
+    mut stats: ResMut<SimulationStats>,
+    dweebs_query: Query<EntityRef, With<Dweeb>>,
+    mut score_reader: EventReader<IncreaseScore>,
+) {
+    for IncreaseScore { dweeb, desk, .. } in score_reader.read() {
+        *stats.ideas.entry(*dweeb).or_default() += 1;
+        *stats.ideas.entry(*desk).or_default() += 1;
+    }
+    if stats.end_reason.is_some() || dweebs_query.is_empty() {
+        // The level is still loading
+        return;
+    }
+    if stats.ticks_left == 0 {
+        stats.end_reason = Some("all ticks simulated");
+        return;
+    }
+    stats.ticks_left -= 1;
+    stats.ticks_simulated += 1;
+
+    for dweeb in dweebs_query.iter() {
+        let Some(BehaviorDescription { name, occupying }) = describe_behavior(&dweeb) else {
+            continue;
+        };
+        *stats
+            .behavior_ticks
+            .entry(dweeb.id())
+            .or_default()
+            .entry(name)
+            .or_default() += 1;
+        let previously_occupying = stats.occupying.insert(dweeb.id(), occupying).flatten();
+        if let Some(furniture) = occupying {
+            if previously_occupying != Some(furniture) {
+                *stats.furniture_uses.entry(furniture).or_default() += 1;
+            }
+        }
+    }
+}
+
+fn report_and_exit(
+    stats: Res<SimulationStats>,
+    game_data: Res<GameData>,
+    fixed_time: Res<Time<Fixed>>,
+    furniture_query: Query<(Entity, &GlobalTransform, Has<Bed>), Or<(With<Bed>, With<Desk>)>>,
+    mut exit: EventWriter<AppExit>,
+) {
+    let tick_secs = fixed_time.timestep().as_secs_f32();
+    println!(
+        "Simulation ended ({}) after {} ticks ({:.1} seconds)",
+        stats.end_reason.unwrap_or_default(),
+        stats.ticks_simulated,
+        stats.ticks_simulated as f32 * tick_secs,
+    );
+    println!("Ideas scribed: {}", game_data.ideas_scribed());
+    println!("Score: {}", game_data.score());
+
+    println!("Seconds spent in each behavior:");
+    let mut dweebs = stats.behavior_ticks.iter().collect::<Vec<_>>();
+    dweebs.sort_by_key(|(dweeb, _)| **dweeb);
+    for (dweeb, behavior_ticks) in dweebs {
+        let times = BEHAVIOR_NAMES
+            .iter()
+            .map(|name| {
+                let ticks = behavior_ticks.get(name).copied().unwrap_or_default();
+                format!("{name}={:.1}", ticks as f32 * tick_secs)
+            })
+            .collect::<Vec<_>>();
+        println!(
+            "  Dweeb {dweeb}: {} ideas={}",
+            times.join(" "),
+            stats.ideas.get(dweeb).copied().unwrap_or_default(),
+        );
+    }
+
+    for (kind, want_beds) in [("Bed", true), ("Desk", false)] {
+        println!("{kind} uses:");
+        for (entity, transform, is_bed) in furniture_query.iter() {
+            if is_bed != want_beds {
+                continue;
+            }
+            let position = transform.translation();
+            let uses = stats
+                .furniture_uses
+                .get(&entity)
+                .copied()
+                .unwrap_or_default();
+            if is_bed {
+                println!("  {kind} at ({:.1}, {:.1}): {uses}", position.x, position.z);
+            } else {
+                println!(
+                    "  {kind} at ({:.1}, {:.1}): {uses} ({} ideas)",
+                    position.x,
+                    position.z,
+                    stats.ideas.get(&entity).copied().unwrap_or_default(),
+                );
+            }
+        }
+    }
+
+    exit.send(AppExit::Success);
+}
@@ -1,6 +1,7 @@
 use bevy::{ecs::query::QueryFilter, prelude::*};
+use bevy_egui::egui;
 use bevy_yoleck::prelude::*;
-use bevy_yoleck::vpeol_3d::Vpeol3dPosition;
+use bevy_yoleck::vpeol_3d::{Vpeol3dPosition, Vpeol3dRotation};
 
 pub fn affix_vpeol_y<F: 'static + QueryFilter>(app: &mut App, y_value: f32) {
     app.add_yoleck_edit_system(move |mut query: Query<&mut Vpeol3dPosition, F>| {
@@ -9,3 +10,28 @@ pub fn affix_vpeol_y<F: 'static + QueryFilter>(app: &mut App, y_value: f32) {
         }
     });
 }
+
+/// Lets the level editor rotate the entity around the vertical axis (the only rotation that makes
+/// sense for things that stand on the floor)
+pub fn edit_vpeol_yaw<F: 'static + QueryFilter>(app: &mut App) {
+    app.add_yoleck_edit_system(
+        move |mut ui: ResMut<YoleckUi>, mut edit: YoleckEdit<&mut Vpeol3dRotation, F>| {
+            let Ok(mut rotation) = edit.get_single_mut() else {
+                return;
+            };
+            let (yaw, _, _) = rotation.0.to_euler(EulerRot::YXZ);
+            let mut yaw_degrees = yaw.to_degrees().round();
+            if ui
+                .add(
+                    egui::Slider::new(&mut yaw_degrees, -180.0..=180.0)
+                        .step_by(15.0)
+                        .suffix("°")
+                        .text("Yaw"),
+                )
+                .changed()
+            {
+                rotation.0 = Quat::from_rotation_y(yaw_degrees.to_radians());
+            }
+        },
+    );
+}
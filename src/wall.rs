@@ -0,0 +1,86 @@
+use avian3d::prelude::*;
+use bevy::{color::palettes::css, prelude::*};
+use bevy_egui::egui;
+use bevy_yoleck::{
+    prelude::*,
+    vpeol_3d::{Vpeol3dPosition, Vpeol3dRotation},
+};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    floor::{floor_top_under, Floor, FloorShape, FLOOR_THICKNESS},
+    util::edit_vpeol_yaw,
+};
+
+pub struct WallPlugin;
+
+impl Plugin for WallPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_yoleck_entity_type({
+            YoleckEntityType::new("Wall")
+                .with::<Vpeol3dPosition>()
+                .with::<Vpeol3dRotation>()
+                .with::<WallSize>()
+                .insert_on_init(|| Wall)
+        });
+        app.add_yoleck_edit_system(edit_wall_size);
+        edit_vpeol_yaw::<With<Wall>>(app);
+        app.add_systems(YoleckSchedule::Populate, populate_wall);
+    }
+}
+
+#[derive(Component)]
+pub struct Wall;
+
+#[derive(Clone, PartialEq, Serialize, Deserialize, Component, YoleckComponent)]
+#[serde(default)]
+pub struct WallSize {
+    pub size: Vec3,
+}
+
+impl Default for WallSize {
+    fn default() -> Self {
+        Self {
+            size: Vec3::new(4.0, 3.0, 0.5),
+        }
+    }
+}
+
+fn edit_wall_size(
+    mut ui: ResMut<YoleckUi>,
+    mut edit: YoleckEdit<(&mut WallSize, &mut Vpeol3dPosition)>,
+    floors_query: Query<(&GlobalTransform, &FloorShape), With<Floor>>,
+) {
+    let Ok((mut wall_size, mut position)) = edit.get_single_mut() else {
+        return;
+    };
+    ui.add(egui::Slider::new(&mut wall_size.size.x, 0.5..=50.0).text("Length"));
+    ui.add(egui::Slider::new(&mut wall_size.size.y, 0.5..=10.0).text("Height"));
+    ui.add(egui::Slider::new(&mut wall_size.size.z, 0.25..=10.0).text("Thickness"));
+    // Keep it standing on the floor under it (or where a floor at the default height would be)
+    let bottom = position.0 - 0.5 * wall_size.size.y * Vec3::Y;
+    let floor_top = floor_top_under(bottom, floors_query.iter()).unwrap_or(0.5 * FLOOR_THICKNESS);
+    position.0.y = floor_top + 0.5 * wall_size.size.y;
+}
+
+fn populate_wall(
+    mut pupulate: YoleckPopulate<&WallSize, With<Wall>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+) {
+    pupulate.populate(|ctx, mut cmd, wall_size| {
+        if ctx.is_first_time() {
+            cmd.insert(PbrBundle {
+                material: materials.add(StandardMaterial::from_color(css::BURLYWOOD)),
+                ..Default::default()
+            });
+            cmd.insert(RigidBody::Static);
+        }
+        // The size can change while editing, so these are not only inserted the first time
+        let size = wall_size.size;
+        cmd.insert(meshes.add(Mesh::from(bevy::math::prelude::Cuboid {
+            half_size: 0.5 * size,
+        })));
+        cmd.insert(Collider::cuboid(size.x, size.y, size.z));
+    });
+}